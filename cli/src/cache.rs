@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use infra::gpu::context::GpuContext;
+use rng_core::gpu::input_layout::GpuIvConfig;
+use rng_core::gpu::mt_kernel::run_mt_seedhigh_candidate_set;
+use rng_core::gpu::seed_high_cache::{SeedHighDiskCache, SeedHighKey, SeedHighSet};
+use rng_core::iv_signature::IvSignatureTable;
+
+use crate::error::usage;
+use crate::parse_ivs;
+
+/// 1 つの IV 範囲で事前計算してよい seed_high の見込み件数 (u32 で 256 MiB)
+const MAX_PRECOMPUTE_SEEDS: u64 = 1 << 26;
+
+/// IV が一様に散らばるとみなしたときの、範囲に入る seed_high の見込み件数
+fn expected_seed_count(iv_min: [u8; 6], iv_max: [u8; 6]) -> u64 {
+    let widths: u64 = iv_min
+        .iter()
+        .zip(&iv_max)
+        .map(|(&lo, &hi)| u64::from(hi.saturating_sub(lo)) + 1)
+        .product();
+    // 2^32 * Π(width / 32) = Π(width) * 2^32 / 32^6
+    widths << 2
+}
+
+/// 計算を始める前に、範囲が逆転していないか、結果が大きすぎないかを確かめる
+fn check_precompute_range(iv_min: [u8; 6], iv_max: [u8; 6]) -> Result<(), Box<dyn Error>> {
+    if iv_min.iter().zip(&iv_max).any(|(lo, hi)| lo > hi) {
+        return Err(usage(format!("--iv-min {iv_min:?} exceeds --iv-max {iv_max:?}")));
+    }
+    let expected = expected_seed_count(iv_min, iv_max);
+    if expected > MAX_PRECOMPUTE_SEEDS {
+        return Err(usage(format!(
+            "IV range {iv_min:?}..={iv_max:?} would keep about {expected} seeds (limit {MAX_PRECOMPUTE_SEEDS}); narrow --iv-min/--iv-max"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CacheCommand {
+    /// List cached seed-high candidate sets
+    List,
+    /// Delete all cached seed-high candidate sets
+    Clear,
+    /// Compute and store seed-high candidate sets ahead of a search
+    Precompute {
+        /// MT advance(s) the IVs are taken at
+        #[arg(long = "iv-step", default_values_t = [16, 17])]
+        iv_steps: Vec<u32>,
+        /// Minimum IVs as H,A,B,C,D,S
+        #[arg(long, value_parser = parse_ivs)]
+        iv_min: [u8; 6],
+        /// Maximum IVs as H,A,B,C,D,S
+        #[arg(long, value_parser = parse_ivs)]
+        iv_max: [u8; 6],
+        /// Compute on the CPU from a full IV signature table instead of the GPU
+        #[arg(long)]
+        signature_table: Option<PathBuf>,
+    },
+}
+
+pub(crate) fn run_cache(cache_dir: Option<PathBuf>, command: CacheCommand) -> Result<(), Box<dyn Error>> {
+    let cache = match cache_dir {
+        Some(dir) => SeedHighDiskCache::new(dir),
+        None => SeedHighDiskCache::default_location()
+            .ok_or_else(|| usage("no cache directory; set SUGARTOOLS_CACHE_DIR or pass --cache-dir"))?,
+    };
+
+    match command {
+        CacheCommand::List => {
+            println!("cache_dir={}", cache.dir().display());
+            for e in cache.list()? {
+                println!(
+                    "step={} iv_min={:?} iv_max={:?} seeds={}{} bytes={}",
+                    e.key.iv_step,
+                    e.key.iv_min,
+                    e.key.iv_max,
+                    e.count,
+                    if e.truncated { " (truncated)" } else { "" },
+                    e.bytes
+                );
+            }
+        }
+        CacheCommand::Clear => {
+            let removed = cache.clear()?;
+            println!("removed {} file(s) from {}", removed, cache.dir().display());
+        }
+        CacheCommand::Precompute { iv_steps, iv_min, iv_max, signature_table } => {
+            check_precompute_range(iv_min, iv_max)?;
+            let table = signature_table.map(IvSignatureTable::open).transpose()?;
+            let ctx = match table {
+                Some(_) => None,
+                None => Some(pollster::block_on(GpuContext::new())?),
+            };
+            for iv_step in iv_steps {
+                let key = SeedHighKey { iv_step, iv_min, iv_max };
+                let set = match (&table, &ctx) {
+                    (Some(table), _) => {
+                        if table.step() != iv_step || table.seeds() != (0..1u64 << 32) {
+                            return Err(usage(format!(
+                                "signature table covers step {} seeds {:?}; need step {} over all 2^32 seeds",
+                                table.step(),
+                                table.seeds(),
+                                iv_step
+                            )));
+                        }
+                        SeedHighSet { seeds: table.candidates(iv_min, iv_max), truncated: false }
+                    }
+                    (None, Some(ctx)) => {
+                        let cfg = GpuIvConfig {
+                            iv_step,
+                            _pad0: 0,
+                            iv_min: iv_min.map(u32::from),
+                            iv_max: iv_max.map(u32::from),
+                        };
+                        pollster::block_on(run_mt_seedhigh_candidate_set(ctx, &cfg))?
+                    }
+                    (None, None) => unreachable!("GPU context is created when no table is given"),
+                };
+                cache.store(&key, &set)?;
+                println!(
+                    "step={} seeds={}{}",
+                    iv_step,
+                    set.seeds.len(),
+                    if set.truncated { " (truncated)" } else { "" }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_precompute_ranges_are_rejected_up_front() {
+        assert_eq!(expected_seed_count([0; 6], [31; 6]), 1 << 32);
+        assert!(check_precompute_range([0; 6], [31; 6]).is_err());
+        assert!(check_precompute_range([31, 0, 0, 0, 0, 0], [31; 6]).is_err());
+        assert!(check_precompute_range([31, 31, 31, 0, 31, 0], [31; 6]).is_ok());
+        assert!(check_precompute_range([31; 6], [30; 6]).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::fmt;
+use std::process::ExitCode;
+
+use infra::gpu::GpuError;
+use rng_core::error::ConfigError;
+use search::error::SearchError;
+
+/// CLI 自身が出すエラー
+#[derive(Debug)]
+pub(crate) enum CliError {
+    /// 引数の形式が不正
+    Usage(String),
+    /// Ctrl-C で中断された (それまでの結果は出力済み)
+    Interrupted,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "{msg}"),
+            CliError::Interrupted => write!(f, "interrupted; output contains partial results"),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+pub(crate) fn usage(msg: impl Into<String>) -> Box<dyn Error> {
+    Box::new(CliError::Usage(msg.into()))
+}
+
+/**
+終了コード
+- 1: ファイル読み書きなどその他のエラー
+- 2: 入力が不正 (clap の引数エラーと同じ)
+- 3: GPU のエラー
+- 130: Ctrl-C で中断
+*/
+pub(crate) fn exit_code(e: &(dyn Error + 'static)) -> ExitCode {
+    if let Some(e) = e.downcast_ref::<CliError>() {
+        return match e {
+            CliError::Usage(_) => ExitCode::from(2),
+            CliError::Interrupted => ExitCode::from(130),
+        };
+    }
+    if let Some(e) = e.downcast_ref::<SearchError>() {
+        return match e {
+            SearchError::Gpu(_) => ExitCode::from(3),
+            _ => ExitCode::from(2),
+        };
+    }
+    if e.is::<ConfigError>() {
+        return ExitCode::from(2);
+    }
+    if e.is::<GpuError>() {
+        return ExitCode::from(3);
+    }
+    ExitCode::FAILURE
+}
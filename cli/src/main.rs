@@ -1,19 +1,43 @@
-use std::collections::HashMap;
+mod cache;
+mod error;
+mod needle;
+mod profile;
+mod stream;
+
 use std::error::Error;
-use std::fs;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use cache::{run_cache, CacheCommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use error::{exit_code, usage, CliError};
+use needle::{run_needle, NeedleCommand};
+use profile::{run_profile, ProfileCommand};
+use rng_core::lcg::OffsetType;
 use rng_core::lcg::grotto::Grottos;
 use rng_core::lcg::nature::Nature;
 use rng_core::lcg::wild_poke::WildPoke;
-use rng_core::models::ds_config::DSConfig;
+use rng_core::models::ds_config::{DSConfig, DsConfigFile};
+use rng_core::models::date_set::DateSet;
+use rng_core::models::game_date::GameDate;
 use rng_core::models::game_version::GameVersion;
+use rng_core::models::key_press_filter::KeyPressFilter;
+use rng_core::models::key_presses::KeyPresses;
+use search::black1_pup::{self, PupSearchResult};
+use search::error::SearchError;
+use search::robustness::{Neighbourhood, Robustness};
+use search::sink::ResultSink;
+use search::starter::StarterRequest;
+use search::progress::{CancelToken, SearchControl, SearchProgress};
 use search::white2_tepig::{
-    white2_tepig_dragonite_search, white2_tepig_search, TepigSearchResult,
+    self, white2_tepig_dragonite_search_with_sink, white2_tepig_search_with_sink, BW2Mode,
+    TepigSearchResult,
 };
-use serde::Deserialize;
 use serde::Serialize;
+use stream::{OutputRecord, StreamWriter};
 
 #[derive(Parser)]
 #[command(name = "sugartools")]
@@ -36,33 +60,177 @@ enum Command {
         /// Date in YY-MM-DD (required for normal mode)
         #[arg(long)]
         date: Option<String>,
-        /// Nature (naughty|rash|4|19)
+        /// Dates to scan in dragonite mode, e.g. "2033-08..2035-01,!summer" (summer is always skipped)
         #[arg(long)]
-        nature: String,
+        dates: Option<String>,
+        #[command(flatten)]
+        starter: StarterArgs,
+        #[command(flatten)]
+        keys: KeyFilterArgs,
+        #[command(flatten)]
+        robustness: RobustnessArgs,
         /// Search mode
         #[arg(long, value_enum, default_value_t = TepigMode::Normal)]
         mode: TepigMode,
+        /// Game difficulty selected at new game
+        #[arg(long, value_enum, default_value_t = Difficulty::Normal)]
+        difficulty: Difficulty,
         /// Output format
         #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
         output: OutputFormat,
+        /// Write results to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Stop with an error once more than this many GPU candidates are found
+        #[arg(long)]
+        max_candidates: Option<usize>,
+    },
+    /// Black1 Lillipup search
+    Black1Pup {
+        /// Path to ds_config.json
+        #[arg(long, default_value = "ds_config.json")]
+        config: PathBuf,
+        /// Profile name under ds_configs
+        #[arg(long, default_value = "profile1")]
+        profile: String,
+        /// Maximum wild advances to check after the offset
+        #[arg(long, default_value_t = 70)]
+        wild_max_advances: u32,
+        /// Dates to scan, e.g. "2033..2040,!summer" (defaults to Apr 29-30, Aug 30-31 and Dec 30-31 of every year)
+        #[arg(long)]
+        dates: Option<String>,
+        #[command(flatten)]
+        keys: KeyFilterArgs,
+        #[command(flatten)]
+        robustness: RobustnessArgs,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Write results to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Stop with an error once more than this many GPU candidates are found
+        #[arg(long)]
+        max_candidates: Option<usize>,
+    },
+    /// Manage the on-disk seed-high candidate cache
+    Cache {
+        /// Cache directory (defaults to SUGARTOOLS_CACHE_DIR or the user cache dir)
+        #[arg(long, global = true)]
+        cache_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Predict report needles and find the current advance from observed ones
+    Needle {
+        #[command(subcommand)]
+        command: NeedleCommand,
+    },
+    /// Manage profiles in ds_config.json
+    Profile {
+        /// Path to ds_config.json
+        #[arg(long, global = true, default_value = "ds_config.json")]
+        config: PathBuf,
+        #[command(subcommand)]
+        command: ProfileCommand,
     },
 }
 
+#[derive(Args)]
+struct StarterArgs {
+    /// Nature name or id 0-24
+    #[arg(long)]
+    nature: String,
+    /// Minimum IVs as H,A,B,C,D,S (defaults to the nature's preset, or 0)
+    #[arg(long, value_parser = parse_ivs)]
+    iv_min: Option<[u8; 6]>,
+    /// Maximum IVs as H,A,B,C,D,S (defaults to the nature's preset, or 31)
+    #[arg(long, value_parser = parse_ivs)]
+    iv_max: Option<[u8; 6]>,
+}
+
+#[derive(Args)]
+struct KeyFilterArgs {
+    /// Maximum number of keys held at once
+    #[arg(long)]
+    max_keys: Option<u32>,
+    /// Keys that must not be held, e.g. L+R
+    #[arg(long)]
+    forbid_keys: Option<String>,
+    /// Only use these keys, e.g. A+B+START
+    #[arg(long)]
+    allow_keys: Option<String>,
+    /// Only these combinations, comma-separated, e.g. none,A+START
+    #[arg(long)]
+    keys: Option<String>,
+}
+
+#[derive(Args)]
+struct RobustnessArgs {
+    /// Score each result by how often neighbouring seconds, Timer0 values and key slips still hit,
+    /// and write the results sorted by that score once the search finishes
+    #[arg(long)]
+    robustness: bool,
+    /// Seconds early or late to allow when scoring
+    #[arg(long, default_value_t = 1, requires = "robustness", value_parser = clap::value_parser!(u8).range(0..=127))]
+    robustness_seconds: u8,
+    /// Timer0 values the console boots with, e.g. 0x10F9-0x10FB (defaults to the profile's Timer0)
+    #[arg(long, value_parser = parse_timer0_range, requires = "robustness")]
+    timer0_range: Option<RangeInclusive<u16>>,
+}
+
 #[derive(Copy, Clone, ValueEnum)]
 enum TepigMode {
     Normal,
     Dragonite,
 }
 
+#[derive(Copy, Clone, ValueEnum)]
+enum Difficulty {
+    Normal,
+    Challenge,
+}
+
+impl From<Difficulty> for BW2Mode {
+    fn from(d: Difficulty) -> Self {
+        match d {
+            Difficulty::Normal => BW2Mode::Normal,
+            Difficulty::Challenge => BW2Mode::Challenge,
+        }
+    }
+}
+
+/// 起動後のオフセットの種類
+#[derive(Copy, Clone, ValueEnum)]
+enum OffsetArg {
+    Bw1Start,
+    Bw1Continue,
+    Bw2Start,
+    Bw2StartChallenge,
+    Bw2Continue,
+    Bw2ContinueWithLink,
+}
+
+impl From<OffsetArg> for OffsetType {
+    fn from(o: OffsetArg) -> Self {
+        match o {
+            OffsetArg::Bw1Start => OffsetType::Bw1Start,
+            OffsetArg::Bw1Continue => OffsetType::Bw1Continue,
+            OffsetArg::Bw2Start => OffsetType::BW2Start,
+            OffsetArg::Bw2StartChallenge => OffsetType::BW2StartChallengeMode,
+            OffsetArg::Bw2Continue => OffsetType::BW2Continue,
+            OffsetArg::Bw2ContinueWithLink => OffsetType::BW2ContinueWithLink,
+        }
+    }
+}
+
 #[derive(Copy, Clone, ValueEnum)]
 enum OutputFormat {
     Text,
     Json,
-}
-
-#[derive(Deserialize)]
-struct DsConfigFile {
-    ds_configs: HashMap<String, DSConfig>,
+    /// One JSON object per line, written as each result is found
+    Ndjson,
+    Csv,
 }
 
 #[derive(Serialize)]
@@ -75,13 +243,48 @@ struct OutputResult {
     hour: u8,
     minute: u8,
     second: u8,
-    key_presses: String,
+    key_presses: KeyPresses,
     ivs: [u8; 6],
     tepig_iv_step: u8,
     tepig_frames: Vec<u32>,
     candy_frames: Vec<CandyFrame>,
     pidove_frames: Vec<WildFrame>,
     psyduck_frames: Vec<WildFrame>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    robustness: Option<RobustnessOutput>,
+}
+
+#[derive(Serialize)]
+struct PupOutputResult {
+    seed0: u64,
+    seed1: u64,
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    key_presses: KeyPresses,
+    ivs: [u8; 6],
+    wild_advances: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    robustness: Option<RobustnessOutput>,
+}
+
+#[derive(Serialize)]
+struct RobustnessOutput {
+    score: f64,
+    neighbours: Vec<NeighbourOutput>,
+}
+
+#[derive(Serialize)]
+struct NeighbourOutput {
+    second_offset: i8,
+    timer0: u16,
+    key_presses: KeyPresses,
+    key_slip: bool,
+    weight: f64,
+    hit: bool,
 }
 
 #[derive(Serialize)]
@@ -110,28 +313,63 @@ struct WildFrame {
     gender: Option<u8>,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    match cli.command {
+    let result = match cli.command {
         Command::White2Tepig {
             config,
             profile,
             date,
-            nature,
+            dates,
+            starter,
+            keys,
+            robustness,
             mode,
+            difficulty,
             output,
-        } => run_white2_tepig(config, profile, date, nature, mode, output),
+            out,
+            max_candidates,
+        } => run_white2_tepig(config, profile, date, dates, starter, keys, robustness, mode, difficulty, output, out, max_candidates),
+        Command::Black1Pup {
+            config,
+            profile,
+            dates,
+            wild_max_advances,
+            keys,
+            robustness,
+            output,
+            out,
+            max_candidates,
+        } => run_black1_pup(config, profile, dates, wild_max_advances, keys, robustness, output, out, max_candidates),
+        Command::Cache { cache_dir, command } => run_cache(cache_dir, command),
+        Command::Needle { command } => run_needle(command),
+        Command::Profile { config, command } => run_profile(config, command),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            exit_code(e.as_ref())
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_white2_tepig(
     config_path: PathBuf,
     profile: String,
     date: Option<String>,
-    nature: String,
+    dates: Option<String>,
+    starter: StarterArgs,
+    keys: KeyFilterArgs,
+    robustness: RobustnessArgs,
     mode: TepigMode,
+    difficulty: Difficulty,
     output: OutputFormat,
+    out: Option<PathBuf>,
+    max_candidates: Option<usize>,
 ) -> Result<(), Box<dyn Error>> {
     let ds_config = load_ds_config(&config_path, &profile)?;
     if ds_config.Version != GameVersion::White2 {
@@ -141,117 +379,382 @@ fn run_white2_tepig(
         );
     }
 
-    let nat = parse_nature(&nature)?;
+    let request = build_starter_request(&starter)?;
+    let key_filter = build_key_filter(&keys)?;
+    let bw2_mode = BW2Mode::from(difficulty);
+
+    // 日付の形式エラーは検索を始める前に出す
+    let date = match mode {
+        TepigMode::Normal if dates.is_some() => return Err(usage("--dates is only used in dragonite mode")),
+        TepigMode::Normal => Some(parse_date(date)?),
+        TepigMode::Dragonite => None,
+    };
+    let dates = parse_dates(dates)?.unwrap_or_else(DateSet::all);
+    let neighbourhood = build_neighbourhood(&robustness, &ds_config);
+
+    let mut sink = StreamWriter::start::<TepigSearchResult>(open_output(out.as_ref())?, output)?;
+    let cancel = install_ctrlc()?;
+    let mut control = search_control(cancel.clone(), max_candidates);
+    let mut found = Vec::new();
+    let mut target: &mut dyn ResultSink<TepigSearchResult> = if neighbourhood.is_some() { &mut found } else { &mut sink };
+    let result = match date {
+        Some(date) => pollster::block_on(white2_tepig_search_with_sink(
+            ds_config, date.year(), date.month(), date.day(), &request, &key_filter, bw2_mode, &mut target, &mut control,
+        )),
+        None => pollster::block_on(white2_tepig_dragonite_search_with_sink(
+            ds_config, &dates, &request, &key_filter, bw2_mode, &mut target, &mut control,
+        )),
+    };
+    if let Some(n) = &neighbourhood {
+        let dragonite = matches!(mode, TepigMode::Dragonite);
+        for r in &mut found {
+            r.robustness = Some(white2_tepig::robustness(ds_config, r, &request, bw2_mode, dragonite, n));
+        }
+        emit_by_score(found, &mut sink, |r| r.robustness.as_ref().map_or(0.0, |rb| rb.score));
+    }
+    finish_search(sink, out.as_ref(), &cancel, result)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_black1_pup(
+    config_path: PathBuf,
+    profile: String,
+    dates: Option<String>,
+    wild_max_advances: u32,
+    keys: KeyFilterArgs,
+    robustness: RobustnessArgs,
+    output: OutputFormat,
+    out: Option<PathBuf>,
+    max_candidates: Option<usize>,
+) -> Result<(), Box<dyn Error>> {
+    let ds_config = load_ds_config(&config_path, &profile)?;
+    if ds_config.Version != GameVersion::Black {
+        eprintln!(
+            "warning: profile '{}' is {:?}, expected Black",
+            profile, ds_config.Version
+        );
+    }
+    let key_filter = build_key_filter(&keys)?;
+    let dates = parse_dates(dates)?.unwrap_or_else(black1_pup::target_dates);
+    let neighbourhood = build_neighbourhood(&robustness, &ds_config);
 
-    let results = match mode {
-        TepigMode::Normal => {
-            let (year, month, day) = parse_date(date)?;
-            pollster::block_on(async { white2_tepig_search(ds_config, year, month, day, nat).await })
+    let mut sink = StreamWriter::start::<PupSearchResult>(open_output(out.as_ref())?, output)?;
+    let cancel = install_ctrlc()?;
+    let mut control = search_control(cancel.clone(), max_candidates);
+    let mut found = Vec::new();
+    let mut target: &mut dyn ResultSink<PupSearchResult> = if neighbourhood.is_some() { &mut found } else { &mut sink };
+    let result = pollster::block_on(black1_pup::search_with_sink(ds_config, &dates, wild_max_advances, &key_filter, &mut target, &mut control));
+    if let Some(n) = &neighbourhood {
+        for r in &mut found {
+            r.robustness = Some(black1_pup::robustness(ds_config, r, wild_max_advances, n));
         }
-        TepigMode::Dragonite => {
-            pollster::block_on(async { white2_tepig_dragonite_search(ds_config, nat).await })
+        emit_by_score(found, &mut sink, |r| r.robustness.as_ref().map_or(0.0, |rb| rb.score));
+    }
+    finish_search(sink, out.as_ref(), &cancel, result)
+}
+
+/// `--robustness` がなければ None (結果は見つかった順にそのまま流す)
+fn build_neighbourhood(args: &RobustnessArgs, ds_config: &DSConfig) -> Option<Neighbourhood> {
+    if !args.robustness {
+        return None;
+    }
+    let n = Neighbourhood::new(ds_config).with_seconds(args.robustness_seconds);
+    Some(match &args.timer0_range {
+        Some(range) => n.with_timer0(range.clone()),
+        None => n,
+    })
+}
+
+/// 取りやすさの高い順に書き出す (同点は見つかった順)
+fn emit_by_score<T>(mut found: Vec<T>, sink: &mut impl ResultSink<T>, score: impl Fn(&T) -> f64) {
+    found.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    for r in found {
+        sink.emit(r);
+    }
+}
+
+/// 検索が失敗・中断しても、それまでに見つかった結果は閉じて書き出す
+fn finish_search<W: Write>(
+    sink: StreamWriter<W>,
+    out: Option<&PathBuf>,
+    cancel: &CancelToken,
+    result: Result<(), SearchError>,
+) -> Result<(), Box<dyn Error>> {
+    if io::stderr().is_terminal() {
+        eprintln!();
+    }
+    sink.finish()?;
+    report_written(out);
+    result?;
+    if cancel.is_cancelled() {
+        return Err(CliError::Interrupted.into());
+    }
+    Ok(())
+}
+
+/// 1回目の Ctrl-C は今のバッチが終わったところで止める。2回目は即終了
+fn install_ctrlc() -> Result<CancelToken, Box<dyn Error>> {
+    let cancel = CancelToken::new();
+    let handler_token = cancel.clone();
+    ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() {
+            std::process::exit(130);
         }
+        handler_token.cancel();
+    })?;
+    Ok(cancel)
+}
+
+/// stderr が端末のときだけ進捗行を出す
+fn search_control(cancel: CancelToken, max_candidates: Option<usize>) -> SearchControl<'static> {
+    let control = SearchControl::new()
+        .with_cancel(cancel)
+        .with_candidate_limit(max_candidates);
+    if io::stderr().is_terminal() {
+        control.on_progress(render_progress)
+    } else {
+        control
+    }
+}
+
+fn render_progress(p: &SearchProgress) {
+    let eta = match p.eta() {
+        Some(d) => format_duration(d.as_secs()),
+        None => "--:--:--".to_string(),
     };
+    eprint!(
+        "\r{:5.1}% dates {}/{} candidates {} results {} {:.1} Mseeds/s ETA {}  ",
+        p.fraction() * 100.0,
+        p.dates_done,
+        p.dates_total,
+        p.candidates,
+        p.results,
+        p.seeds_per_sec() / 1e6,
+        eta,
+    );
+}
 
-    match output {
-        OutputFormat::Text => print_text(&results),
-        OutputFormat::Json => print_json(&results)?,
+fn format_duration(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// `--out` があればファイル、なければ stdout に書く
+fn open_output(out: Option<&PathBuf>) -> Result<Box<dyn Write>, Box<dyn Error>> {
+    match out {
+        Some(path) => Ok(Box::new(BufWriter::new(File::create(path)?))),
+        None => Ok(Box::new(BufWriter::new(io::stdout().lock()))),
     }
+}
 
-    Ok(())
+fn report_written(out: Option<&PathBuf>) {
+    if let Some(path) = out {
+        eprintln!("wrote {}", path.display());
+    }
 }
 
 fn load_ds_config(path: &PathBuf, profile: &str) -> Result<DSConfig, Box<dyn Error>> {
-    let text = fs::read_to_string(path)?;
-    let file: DsConfigFile = serde_json::from_str(&text)?;
+    let file = DsConfigFile::load(path).map_err(|e| format!("{}: {e}", path.display()))?;
     let cfg = file
-        .ds_configs
         .get(profile)
         .ok_or_else(|| format!("profile '{}' not found in {}", profile, path.display()))?;
-    Ok(*cfg)
+    Ok(cfg)
 }
 
-fn parse_nature(s: &str) -> Result<Nature, Box<dyn Error>> {
-    let lower = s.trim().to_lowercase();
-    let id = match lower.as_str() {
-        "naughty" => 4,
-        "rash" => 19,
-        _ => lower.parse::<u8>().map_err(|_| "invalid nature")?,
+/// IV を指定しなければ性格のプリセット、片方だけならもう片方は 0 / 31
+fn build_starter_request(args: &StarterArgs) -> Result<StarterRequest, Box<dyn Error>> {
+    let nature: Nature = args.nature.parse()?;
+    let request = match (args.iv_min, args.iv_max) {
+        (None, None) => StarterRequest::preset(nature)?,
+        (min, max) => StarterRequest::new(nature, min.unwrap_or([0; 6]), max.unwrap_or([31; 6]))?,
     };
-    if id != 4 && id != 19 {
-        return Err("nature must be naughty|rash|4|19".into());
+    Ok(request)
+}
+
+/// キー入力の指定がひとつもなければ全件を通す。形式エラーは ConfigError (終了コード 2)
+fn build_key_filter(args: &KeyFilterArgs) -> Result<KeyPressFilter, Box<dyn Error>> {
+    let mut filter = KeyPressFilter::all();
+    if let Some(n) = args.max_keys {
+        filter = filter.max_keys(n);
+    }
+    if let Some(keys) = &args.forbid_keys {
+        filter = filter.forbid(keys.parse::<KeyPresses>()?);
+    }
+    if let Some(keys) = &args.allow_keys {
+        filter = filter.allow_only(keys.parse::<KeyPresses>()?);
+    }
+    if let Some(combos) = &args.keys {
+        filter = filter.only_combos(KeyPressFilter::parse_combos(combos)?);
+    }
+    if filter.iter().next().is_none() {
+        return Err(usage("key filter excludes every key combination"));
+    }
+    Ok(filter)
+}
+
+/// "0x10F9-0x10FB" / "4345-4347" / "0x10FA"
+fn parse_timer0_range(s: &str) -> Result<RangeInclusive<u16>, String> {
+    let parse = |v: &str| {
+        let v = v.trim();
+        let parsed = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => v.parse(),
+        };
+        parsed.map_err(|_| format!("invalid Timer0 '{v}'"))
+    };
+    let (min, max) = match s.split_once('-') {
+        Some((a, b)) => (parse(a)?, parse(b)?),
+        None => (parse(s)?, parse(s)?),
+    };
+    if min > max {
+        return Err(format!("Timer0 range '{s}' is empty"));
+    }
+    Ok(min..=max)
+}
+
+/// 16 進の seed ("0x" はあってもなくてもよい)
+fn parse_seed(s: &str) -> Result<u64, String> {
+    let v = s.trim();
+    let hex = v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")).unwrap_or(v);
+    u64::from_str_radix(hex, 16).map_err(|_| format!("invalid seed '{v}' (expected up to 16 hex digits)"))
+}
+
+fn parse_ivs(s: &str) -> Result<[u8; 6], String> {
+    let values = s
+        .split(',')
+        .map(|v| v.trim().parse::<u8>().map_err(|_| format!("invalid IV '{v}'")))
+        .collect::<Result<Vec<_>, _>>()?;
+    values
+        .try_into()
+        .map_err(|_| "expected 6 comma-separated IVs (H,A,B,C,D,S)".to_string())
+}
+
+/// 空になる指定は検索しても何も出ないので、始める前にエラーにする
+fn parse_dates(dates: Option<String>) -> Result<Option<DateSet>, Box<dyn Error>> {
+    let Some(dates) = dates else {
+        return Ok(None);
+    };
+    let set: DateSet = dates.parse()?;
+    if set.dates().is_empty() {
+        return Err(usage(format!("--dates '{dates}' matches no date")));
     }
-    Ok(Nature::new(id))
+    Ok(Some(set))
 }
 
-fn parse_date(date: Option<String>) -> Result<(u8, u8, u8), Box<dyn Error>> {
-    let date = date.ok_or("date is required in normal mode (use --date YY-MM-DD)")?;
+fn parse_date(date: Option<String>) -> Result<GameDate, Box<dyn Error>> {
+    let date = date.ok_or_else(|| usage("date is required in normal mode (use --date YY-MM-DD)"))?;
     let parts: Vec<&str> = date.split('-').collect();
     if parts.len() != 3 {
-        return Err("date must be YY-MM-DD".into());
+        return Err(usage("date must be YY-MM-DD"));
     }
-    let year = parts[0].parse::<u8>()?;
-    let month = parts[1].parse::<u8>()?;
-    let day = parts[2].parse::<u8>()?;
-    Ok((year, month, day))
+    let field = |p: &str| p.parse::<u8>().map_err(|_| usage(format!("invalid date '{date}'")));
+    Ok(GameDate::try_new(field(parts[0])?, field(parts[1])?, field(parts[2])?)?)
 }
 
-fn print_text(results: &[TepigSearchResult]) {
-    println!("total_results={}", results.len());
-    for r in results {
-        println!(
+impl OutputRecord for TepigSearchResult {
+    type Json = OutputResult;
+
+    fn to_json(&self) -> OutputResult {
+        to_output(self)
+    }
+
+    fn write_text(&self, w: &mut dyn Write) -> io::Result<()> {
+        let r = self;
+        writeln!(
+            w,
             "seed0={:016X} seed1={:016X} date={:02}/{:02} {:02}:{:02}:{:02} kp={}",
             r.seed0, r.seed1, r.month, r.day, r.hour, r.minute, r.second, r.key_presses
-        );
-        println!("ivs={:?} iv_step={}", r.ivs, r.tepig_iv_step);
-        println!("tepig_frames={:?}", r.tepig_frames);
+        )?;
+        writeln!(w, "ivs={:?} iv_step={}", r.ivs, r.tepig_iv_step)?;
+        if let Some(rb) = &r.robustness {
+            writeln!(w, "robustness={:.4} {}", rb.score, rb.map())?;
+        }
+        writeln!(w, "tepig_frames={:?}", r.tepig_frames)?;
 
-        print!("pidove_frames=");
+        write!(w, "pidove_frames=")?;
         for (frame, poke) in &r.pidove_frames {
             let nature = poke.nature.as_ref().map(|n| n.name()).unwrap_or("None");
             let lv = if poke.slot.is_some_and(|s| s < 20) { "Lv.2" } else { "Lv.4" };
-            print!("{}:{}:{} ", frame, lv, nature);
+            write!(w, "{}:{}:{} ", frame, lv, nature)?;
         }
-        println!();
+        writeln!(w)?;
 
-        print!("psyduck_frames=");
+        write!(w, "psyduck_frames=")?;
         for (frame, poke) in &r.psyduck_frames {
             let nature = poke.nature.as_ref().map(|n| n.name()).unwrap_or("None");
-            print!("{}:{} ", frame, nature);
+            write!(w, "{}:{} ", frame, nature)?;
         }
-        println!();
+        writeln!(w)?;
 
-        println!("candy_frames:");
+        writeln!(w, "candy_frames:")?;
         for (frame, grottos) in &r.candy_frames {
-            print!("  {}: ", frame);
+            write!(w, "  {}: ", frame)?;
             let mut any = false;
             for i in 0..grottos.grottos.len() {
                 let g = grottos.get(i).unwrap_or_default();
                 if g.slot().is_some() {
                     any = true;
-                    print!(
+                    write!(
+                        w,
                         "#{}(sub={:?},slot={:?},gender={:?}) ",
                         i,
                         g.sub_slot(),
                         g.slot(),
                         g.gender()
-                    );
+                    )?;
                 }
             }
             if !any {
-                print!("(none)");
+                write!(w, "(none)")?;
             }
-            println!();
+            writeln!(w)?;
         }
-        println!();
+        writeln!(w)
     }
 }
 
-fn print_json(results: &[TepigSearchResult]) -> Result<(), Box<dyn Error>> {
-    let out: Vec<OutputResult> = results.iter().map(to_output).collect();
-    let text = serde_json::to_string_pretty(&out)?;
-    println!("{text}");
-    Ok(())
+impl OutputRecord for PupSearchResult {
+    type Json = PupOutputResult;
+
+    fn to_json(&self) -> PupOutputResult {
+        let r = self;
+        PupOutputResult {
+            seed0: r.seed0,
+            seed1: r.seed1,
+            year: r.year,
+            month: r.month,
+            day: r.day,
+            hour: r.hour,
+            minute: r.minute,
+            second: r.second,
+            key_presses: r.key_presses,
+            ivs: r.ivs,
+            wild_advances: r.wild_advances.clone(),
+            robustness: r.robustness.as_ref().map(to_robustness_output),
+        }
+    }
+
+    fn write_text(&self, w: &mut dyn Write) -> io::Result<()> {
+        let r = self;
+        writeln!(
+            w,
+            "seed0={:016X} seed1={:016X} date={:02}/{:02}/{:02} {:02}:{:02}:{:02} kp={} ivs={:?} advances={:?}",
+            r.seed0,
+            r.seed1,
+            r.year,
+            r.month,
+            r.day,
+            r.hour,
+            r.minute,
+            r.second,
+            r.key_presses,
+            r.ivs,
+            r.wild_advances
+        )?;
+        if let Some(rb) = &r.robustness {
+            writeln!(w, "  robustness={:.4} {}", rb.score, rb.map())?;
+        }
+        Ok(())
+    }
 }
 
 fn to_output(r: &TepigSearchResult) -> OutputResult {
@@ -264,7 +767,7 @@ fn to_output(r: &TepigSearchResult) -> OutputResult {
         hour: r.hour,
         minute: r.minute,
         second: r.second,
-        key_presses: r.key_presses.clone(),
+        key_presses: r.key_presses,
         ivs: r.ivs,
         tepig_iv_step: r.tepig_iv_step,
         tepig_frames: r.tepig_frames.clone(),
@@ -278,6 +781,25 @@ fn to_output(r: &TepigSearchResult) -> OutputResult {
             .collect(),
         pidove_frames: r.pidove_frames.iter().map(to_wild_frame).collect(),
         psyduck_frames: r.psyduck_frames.iter().map(to_wild_frame).collect(),
+        robustness: r.robustness.as_ref().map(to_robustness_output),
+    }
+}
+
+fn to_robustness_output(r: &Robustness) -> RobustnessOutput {
+    RobustnessOutput {
+        score: r.score,
+        neighbours: r
+            .cells
+            .iter()
+            .map(|c| NeighbourOutput {
+                second_offset: c.second_offset,
+                timer0: c.timer0,
+                key_presses: c.key_presses,
+                key_slip: c.key_slip,
+                weight: c.weight,
+                hit: c.hit,
+            })
+            .collect(),
     }
 }
 
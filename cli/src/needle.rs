@@ -0,0 +1,183 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use infra::file::CsvRecord;
+use rng_core::lcg::needle::{find_needle_advances, Needle, NeedleMatch};
+use rng_core::lcg::{lcg_advance, Lcg, OffsetType};
+use search::sink::ResultSink;
+use serde::Serialize;
+
+use crate::error::usage;
+use crate::stream::{OutputRecord, StreamWriter};
+use crate::{open_output, parse_seed, report_written, OffsetArg, OutputFormat};
+
+#[derive(Subcommand)]
+pub(crate) enum NeedleCommand {
+    /// Print the report needles shown at each advance
+    Show {
+        /// Initial seed (hex)
+        #[arg(long, value_parser = parse_seed)]
+        seed0: u64,
+        /// Count advances after this offset instead of from seed0
+        #[arg(long, value_enum)]
+        offset: Option<OffsetArg>,
+        /// First advance to show
+        #[arg(long, default_value_t = 0)]
+        from: u64,
+        /// Number of needles to show
+        #[arg(long, default_value_t = 100)]
+        count: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Write results to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Find the advance(s) where the observed needles appear
+    Find {
+        /// Candidate initial seeds (hex); repeat or separate with commas
+        #[arg(long = "seed0", value_parser = parse_seed, value_delimiter = ',', required = true)]
+        seeds: Vec<u64>,
+        /// Observed needles in order, e.g. "03725", "↑→↘" or "N,E,SE"
+        #[arg(long)]
+        observed: String,
+        /// Count advances after this offset instead of from seed0
+        #[arg(long, value_enum)]
+        offset: Option<OffsetArg>,
+        /// Earliest advance the first needle may have been shown at
+        #[arg(long, default_value_t = 0)]
+        from: u64,
+        /// Latest advance the first needle may have been shown at
+        #[arg(long, default_value_t = 1000)]
+        to: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Write results to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// ある消費数で開いた針 (`advance` はオフセットからの消費数)
+struct NeedleRow {
+    advance: u64,
+    needle: Needle,
+}
+
+#[derive(Serialize)]
+struct NeedleRowOutput {
+    advance: u64,
+    needle: u8,
+    arrow: String,
+}
+
+impl CsvRecord for NeedleRow {
+    fn header() -> &'static [&'static str] {
+        &["advance", "needle", "arrow"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.advance.to_string(), self.needle.id().to_string(), self.needle.to_string()]
+    }
+}
+
+impl OutputRecord for NeedleRow {
+    type Json = NeedleRowOutput;
+
+    fn to_json(&self) -> NeedleRowOutput {
+        NeedleRowOutput { advance: self.advance, needle: self.needle.id(), arrow: self.needle.to_string() }
+    }
+
+    fn write_text(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{} {} {}", self.advance, self.needle, self.needle.id())
+    }
+}
+
+/// 見つかった位置。消費数はオフセットから数え直してある
+#[derive(Clone, Copy, Serialize)]
+struct NeedleMatchRow {
+    seed0: u64,
+    advance: u64,
+    current: u64,
+}
+
+impl NeedleMatchRow {
+    fn new(m: NeedleMatch, base: u64) -> Self {
+        Self { seed0: m.seed0, advance: m.advance - base, current: m.current.wrapping_sub(base) }
+    }
+}
+
+impl CsvRecord for NeedleMatchRow {
+    fn header() -> &'static [&'static str] {
+        &["seed0", "advance", "current"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![format!("0x{:016X}", self.seed0), self.advance.to_string(), self.current.to_string()]
+    }
+}
+
+impl OutputRecord for NeedleMatchRow {
+    type Json = NeedleMatchRow;
+
+    fn to_json(&self) -> NeedleMatchRow {
+        *self
+    }
+
+    fn write_text(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "seed0={:016X} advance={} current={}", self.seed0, self.advance, self.current)
+    }
+}
+
+/// `offset` があればオフセット後の消費数 (seed0 から)
+fn offset_advance(seed0: u64, offset: Option<OffsetArg>) -> u64 {
+    offset.map_or(0, |o| Lcg::new(seed0).offset_seed0(OffsetType::from(o)))
+}
+
+/// `base + from` から `len` 個の消費数。あふれるなら使い方の誤り
+fn advance_range(base: u64, from: u64, len: u64) -> Result<std::ops::Range<u64>, Box<dyn Error>> {
+    let start = base.checked_add(from).ok_or_else(|| usage("--from is too large"))?;
+    let end = start.checked_add(len).ok_or_else(|| usage("the advance range is too large"))?;
+    Ok(start..end)
+}
+
+pub(crate) fn run_needle(command: NeedleCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        NeedleCommand::Show { seed0, offset, from, count, output, out } => {
+            let base = offset_advance(seed0, offset);
+            let range = advance_range(base, from, count)?;
+            let mut sink = StreamWriter::start::<NeedleRow>(open_output(out.as_ref())?, output)?;
+            // 針を全部持たずに 1 つずつ書き出す
+            let mut lcg = Lcg { state: lcg_advance(seed0, range.start), step: range.start };
+            for advance in from..from + count {
+                sink.emit(NeedleRow { advance, needle: lcg.get_needle() });
+            }
+            sink.finish()?;
+            report_written(out.as_ref());
+        }
+        NeedleCommand::Find { seeds, observed, offset, from, to, output, out } => {
+            let observed = Needle::parse_sequence(&observed)?;
+            if observed.is_empty() {
+                return Err(usage("--observed must contain at least one needle"));
+            }
+            if from > to {
+                return Err(usage("--from must not be greater than --to"));
+            }
+            let len = (to - from).checked_add(1).ok_or_else(|| usage("--to is too large"))?;
+            let mut sink = StreamWriter::start::<NeedleMatchRow>(open_output(out.as_ref())?, output)?;
+            for seed0 in seeds {
+                let base = offset_advance(seed0, offset);
+                for m in find_needle_advances(&[seed0], advance_range(base, from, len)?, &observed) {
+                    sink.emit(NeedleMatchRow::new(m, base));
+                }
+            }
+            sink.finish()?;
+            report_written(out.as_ref());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use rng_core::error::ConfigError;
+use rng_core::models::ds_config::{parse_mac, DSConfig, DsConfigFile};
+use rng_core::models::game_version::GameVersion;
+use rng_core::models::hardware::Hardware;
+
+use crate::error::usage;
+use crate::parse_timer0_range;
+
+#[derive(Subcommand)]
+pub(crate) enum ProfileCommand {
+    /// List the profiles in ds_config.json
+    List,
+    /// Print one profile as JSON
+    Show { name: String },
+    /// Add a profile (or replace it with --force)
+    Add {
+        name: String,
+        /// Black, White, Black2 or White2
+        #[arg(long, value_parser = parse_version)]
+        version: GameVersion,
+        /// Timer0, e.g. 0x10FA
+        #[arg(long, value_parser = parse_timer0)]
+        timer0: u16,
+        /// ds or ds_lite
+        #[arg(long, value_parser = parse_hardware)]
+        hardware: Hardware,
+        /// MAC address, e.g. 00:09:BF:6D:93:CE or 0x0009BF6D93CE
+        #[arg(long, value_parser = parse_mac_arg)]
+        mac: u64,
+        /// Replace an existing profile with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove a profile
+    Remove { name: String },
+    /// Check MAC and Timer0 of one or all profiles
+    Validate { name: Option<String> },
+}
+
+fn parse_version(s: &str) -> Result<GameVersion, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "black" | "b" => Ok(GameVersion::Black),
+        "white" | "w" => Ok(GameVersion::White),
+        "black2" | "b2" => Ok(GameVersion::Black2),
+        "white2" | "w2" => Ok(GameVersion::White2),
+        _ => Err(format!("unknown version '{s}' (expected Black, White, Black2 or White2)")),
+    }
+}
+
+fn parse_timer0(s: &str) -> Result<u16, String> {
+    let range = parse_timer0_range(s)?;
+    if range.start() != range.end() {
+        return Err(format!("expected a single Timer0, got '{s}'"));
+    }
+    Ok(*range.start())
+}
+
+fn parse_hardware(s: &str) -> Result<Hardware, String> {
+    s.parse().map_err(|e| format!("{e}"))
+}
+
+fn parse_mac_arg(s: &str) -> Result<u64, String> {
+    parse_mac(s).map_err(|e| format!("{e}"))
+}
+
+/// ファイルがなければ空から始める (add のとき)
+fn load_or_empty(path: &Path) -> Result<DsConfigFile, Box<dyn Error>> {
+    match DsConfigFile::load(path) {
+        Ok(file) => Ok(file),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DsConfigFile::default()),
+        Err(e) => Err(format!("{}: {e}", path.display()).into()),
+    }
+}
+
+fn load(path: &Path) -> Result<DsConfigFile, Box<dyn Error>> {
+    DsConfigFile::load(path).map_err(|e| format!("{}: {e}", path.display()).into())
+}
+
+fn profile<'a>(file: &'a DsConfigFile, name: &str, path: &Path) -> Result<&'a DSConfig, Box<dyn Error>> {
+    file.ds_configs
+        .get(name)
+        .ok_or_else(|| usage(format!("profile '{}' not found in {}", name, path.display())))
+}
+
+/// Timer0 の目安には出典がないので、外れていても失敗にはしない
+fn is_warning(problem: &ConfigError) -> bool {
+    matches!(problem, ConfigError::ImplausibleTimer0 { .. })
+}
+
+fn describe(name: &str, cfg: &DSConfig) -> String {
+    format!(
+        "{} version={:?} hardware={} timer0=0x{:04X} mac=0x{:012X}",
+        name, cfg.Version, cfg.Hardware, cfg.Timer0, cfg.MAC
+    )
+}
+
+pub(crate) fn run_profile(path: PathBuf, command: ProfileCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        ProfileCommand::List => {
+            let file = load(&path)?;
+            for (name, cfg) in &file.ds_configs {
+                println!("{}", describe(name, cfg));
+            }
+        }
+        ProfileCommand::Show { name } => {
+            let file = load(&path)?;
+            let cfg = profile(&file, &name, &path)?;
+            println!("{}", serde_json::to_string_pretty(cfg)?);
+        }
+        ProfileCommand::Add { name, version, timer0, hardware, mac, force } => {
+            let mut file = load_or_empty(&path)?;
+            if file.ds_configs.contains_key(&name) && !force {
+                return Err(usage(format!("profile '{name}' already exists (use --force to replace it)")));
+            }
+            let cfg = DSConfig::new(version, timer0, hardware, mac);
+            for problem in cfg.validate() {
+                eprintln!("warning: {problem}");
+            }
+            file.ds_configs.insert(name.clone(), cfg);
+            file.save(&path)?;
+            println!("saved {}", describe(&name, &cfg));
+        }
+        ProfileCommand::Remove { name } => {
+            let mut file = load(&path)?;
+            if file.ds_configs.remove(&name).is_none() {
+                return Err(usage(format!("profile '{}' not found in {}", name, path.display())));
+            }
+            file.save(&path)?;
+            println!("removed {}", name);
+        }
+        ProfileCommand::Validate { name } => {
+            let file = load(&path)?;
+            let targets: Vec<(&String, &DSConfig)> = match &name {
+                Some(name) => vec![(name, profile(&file, name, &path)?)],
+                None => file.ds_configs.iter().collect(),
+            };
+            let mut failed = 0;
+            for (name, cfg) in targets {
+                let problems = cfg.validate();
+                if problems.is_empty() {
+                    println!("{name}: ok");
+                    continue;
+                }
+                if problems.iter().any(|p| !is_warning(p)) {
+                    failed += 1;
+                }
+                for problem in problems {
+                    let level = if is_warning(&problem) { "warning" } else { "error" };
+                    println!("{name}: {level}: {problem}");
+                }
+            }
+            if failed > 0 {
+                return Err(usage(format!("{failed} profile(s) failed validation")));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cli_profile_{}_{}.json", name, std::process::id()))
+    }
+
+    fn add(name: &str, timer0: u16, force: bool) -> ProfileCommand {
+        ProfileCommand::Add {
+            name: name.to_string(),
+            version: GameVersion::White2,
+            timer0,
+            hardware: Hardware::DsPhat,
+            mac: 0x0009_BF6D_93CE,
+            force,
+        }
+    }
+
+    #[test]
+    fn add_creates_the_file_and_needs_force_to_replace() {
+        let path = temp_path("add");
+        std::fs::remove_file(&path).ok();
+
+        run_profile(path.clone(), add("main", 0x10FA, false)).unwrap();
+        assert_eq!(DsConfigFile::load(&path).unwrap().get("main").map(|c| c.Timer0), Some(0x10FA));
+
+        assert!(run_profile(path.clone(), add("main", 0x10F9, false)).is_err());
+        assert_eq!(DsConfigFile::load(&path).unwrap().get("main").map(|c| c.Timer0), Some(0x10FA));
+
+        run_profile(path.clone(), add("main", 0x10F9, true)).unwrap();
+        assert_eq!(DsConfigFile::load(&path).unwrap().get("main").map(|c| c.Timer0), Some(0x10F9));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn remove_and_missing_profiles() {
+        let path = temp_path("remove");
+        std::fs::remove_file(&path).ok();
+        assert!(run_profile(path.clone(), ProfileCommand::List).is_err());
+        assert!(run_profile(path.clone(), ProfileCommand::Remove { name: "main".to_string() }).is_err());
+
+        run_profile(path.clone(), add("main", 0x10FA, false)).unwrap();
+        run_profile(path.clone(), add("sub", 0x10F7, false)).unwrap();
+        assert!(run_profile(path.clone(), ProfileCommand::Remove { name: "other".to_string() }).is_err());
+        assert!(run_profile(path.clone(), ProfileCommand::Show { name: "other".to_string() }).is_err());
+
+        run_profile(path.clone(), ProfileCommand::Remove { name: "main".to_string() }).unwrap();
+        let file = DsConfigFile::load(&path).unwrap();
+        assert_eq!(file.names().collect::<Vec<_>>(), ["sub"]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn validate_fails_only_on_errors() {
+        let path = temp_path("validate");
+        // Timer0 が目安から外れているだけなら成功
+        std::fs::write(
+            &path,
+            r#"{ "ds_configs": { "odd": { "version": "White2", "timer0": "0x1270", "hardware": "ds", "mac": 1 } } }"#,
+        )
+        .unwrap();
+        run_profile(path.clone(), ProfileCommand::Validate { name: None }).unwrap();
+
+        std::fs::write(
+            &path,
+            r#"{ "ds_configs": { "wide": { "version": "White2", "timer0": "0x10FA", "hardware": "ds", "mac": "0x1000000000000" } } }"#,
+        )
+        .unwrap();
+        assert!(run_profile(path.clone(), ProfileCommand::Validate { name: None }).is_err());
+        assert!(run_profile(path.clone(), ProfileCommand::Validate { name: Some("none".to_string()) }).is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use infra::file::{CsvRecord, CsvWriter};
+use search::sink::ResultSink;
+use serde::Serialize;
+
+use crate::OutputFormat;
+
+/// CLI から出力できる検索結果
+pub(crate) trait OutputRecord: CsvRecord {
+    type Json: Serialize;
+
+    fn to_json(&self) -> Self::Json;
+    fn write_text(&self, w: &mut dyn Write) -> io::Result<()>;
+}
+
+/**
+検索中に結果を1件ずつ書き出す sink
+
+1件ごとに flush するので、途中で止めてもそこまでの結果は残る
+書き込みエラーは最初の1件だけ覚えておき、`finish` で返す
+*/
+pub(crate) struct StreamWriter<W: Write> {
+    writer: CsvWriter<W>,
+    format: OutputFormat,
+    count: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    pub fn start<T: OutputRecord>(writer: W, format: OutputFormat) -> io::Result<Self> {
+        let mut out = Self {
+            writer: CsvWriter::new(writer),
+            format,
+            count: 0,
+            error: None,
+        };
+        match format {
+            OutputFormat::Csv => out.writer.write_header(T::header())?,
+            OutputFormat::Json => out.writer.get_mut().write_all(b"[")?,
+            OutputFormat::Text | OutputFormat::Ndjson => {}
+        }
+        out.writer.flush()?;
+        Ok(out)
+    }
+
+    /// 末尾を書いて、件数を返す
+    pub fn finish(mut self) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let w = self.writer.get_mut();
+        match self.format {
+            OutputFormat::Text => writeln!(w, "total_results={}", self.count)?,
+            OutputFormat::Json => {
+                if self.count > 0 {
+                    writeln!(w)?;
+                }
+                writeln!(w, "]")?;
+            }
+            OutputFormat::Csv | OutputFormat::Ndjson => {}
+        }
+        self.writer.flush()?;
+        Ok(self.count)
+    }
+
+    fn write_one<T: OutputRecord>(&mut self, result: &T) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Csv => self.writer.write_row(result.row())?,
+            OutputFormat::Text => result.write_text(self.writer.get_mut())?,
+            OutputFormat::Ndjson => {
+                let w = self.writer.get_mut();
+                serde_json::to_writer(&mut *w, &result.to_json())?;
+                writeln!(w)?;
+            }
+            OutputFormat::Json => {
+                let w = self.writer.get_mut();
+                if self.count > 0 {
+                    write!(w, ",")?;
+                }
+                writeln!(w)?;
+                serde_json::to_writer_pretty(&mut *w, &result.to_json())?;
+            }
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W: Write, T: OutputRecord> ResultSink<T> for StreamWriter<W> {
+    fn emit(&mut self, result: T) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.write_one(&result) {
+            Ok(()) => self.count += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
@@ -46,15 +46,30 @@ impl<W: Write> CsvWriter<W> {
         self.writer.write_all(b"\n")?;
         Ok(())
     }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// CSV 以外の行を混ぜて書きたいとき用
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
 }
 
 pub fn write_csv<T: CsvRecord>(path: impl AsRef<Path>, records: &[T]) -> io::Result<()> {
-    let mut writer = CsvWriter::create(path)?;
+    let file = File::create(path)?;
+    write_csv_to(BufWriter::new(file), records)
+}
+
+/// 任意の Write (stdout など) に CSV を書き出す
+pub fn write_csv_to<W: Write, T: CsvRecord>(writer: W, records: &[T]) -> io::Result<()> {
+    let mut writer = CsvWriter::new(writer);
     writer.write_header(T::header())?;
     for record in records {
         writer.write_row(record.row())?;
     }
-    Ok(())
+    writer.flush()
 }
 
 fn escape_field(value: &str) -> String {
@@ -102,4 +117,23 @@ mod tests {
         writer.write_row(["a", "b", "c"]).unwrap();
         assert_eq!(String::from_utf8(out).unwrap(), "a,b,c\n");
     }
+
+    struct Pair(u32, &'static str);
+
+    impl CsvRecord for Pair {
+        fn header() -> &'static [&'static str] {
+            &["id", "name"]
+        }
+
+        fn row(&self) -> Vec<String> {
+            vec![self.0.to_string(), self.1.to_string()]
+        }
+    }
+
+    #[test]
+    fn write_csv_to_writes_header_and_rows() {
+        let mut out = Vec::new();
+        write_csv_to(&mut out, &[Pair(1, "a"), Pair(2, "b,c")]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id,name\n1,a\n2,\"b,c\"\n");
+    }
 }
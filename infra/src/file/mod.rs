@@ -1,2 +1,2 @@
 mod csv;
-pub use csv::{CsvRecord, CsvWriter, write_csv};
+pub use csv::{CsvRecord, CsvWriter, write_csv, write_csv_to};
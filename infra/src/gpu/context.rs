@@ -1,12 +1,14 @@
 use wgpu::{self};
 
+use super::GpuError;
+
 pub struct GpuContext {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
 }
 
 impl GpuContext {
-    pub async fn new() -> Self {
+    pub async fn new() -> Result<Self, GpuError> {
         pollster::block_on(async {
             let instance = wgpu::Instance::default();
             let adapter = instance
@@ -15,8 +17,7 @@ impl GpuContext {
                     compatible_surface: None,
                     force_fallback_adapter: false,
                 })
-                .await
-                .expect("No suitable GPU adapter found");
+                .await?;
 
             let (device, queue) = adapter
                 .request_device(&wgpu::DeviceDescriptor {
@@ -25,10 +26,9 @@ impl GpuContext {
                     required_limits: wgpu::Limits::default(),
                     ..Default::default()
                 })
-                .await
-                .expect("Failed to create device");
+                .await?;
 
-            Self { device, queue}
+            Ok(Self { device, queue})
         })
     }
 }
@@ -40,7 +40,7 @@ mod tests {
     #[test]
     fn gpu_context_can_be_created(){
         pollster::block_on(async {
-            let _ctx = GpuContext::new().await;
+            let _ctx = GpuContext::new().await.expect("GPU context");
         });
     }
 }
@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// GPU の初期化・読み戻しで起きるエラー
+#[derive(Debug)]
+pub enum GpuError {
+    /// 使える GPU アダプタがない
+    NoAdapter(wgpu::RequestAdapterError),
+    /// デバイスを作れなかった (SHADER_INT64 非対応など)
+    RequestDevice(wgpu::RequestDeviceError),
+    /// 結果バッファの読み戻しに失敗した
+    BufferAsync(wgpu::BufferAsyncError),
+    /// 候補数がユーザー指定の上限を超えた (結果は返さない)
+    Truncated { limit: usize },
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter(e) => write!(f, "no suitable GPU adapter found: {e}"),
+            GpuError::RequestDevice(e) => write!(f, "failed to create GPU device: {e}"),
+            GpuError::BufferAsync(e) => write!(f, "failed to read back GPU buffer: {e}"),
+            GpuError::Truncated { limit } => write!(
+                f,
+                "more than {limit} candidates; tighten the search conditions or raise the limit"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GpuError::NoAdapter(e) => Some(e),
+            GpuError::RequestDevice(e) => Some(e),
+            GpuError::BufferAsync(e) => Some(e),
+            GpuError::Truncated { .. } => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestAdapterError> for GpuError {
+    fn from(e: wgpu::RequestAdapterError) -> Self {
+        GpuError::NoAdapter(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for GpuError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        GpuError::RequestDevice(e)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for GpuError {
+    fn from(e: wgpu::BufferAsyncError) -> Self {
+        GpuError::BufferAsync(e)
+    }
+}
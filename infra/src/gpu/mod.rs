@@ -1,4 +1,5 @@
 pub mod context;
+pub mod error;
 pub mod pipeline_factory;
 pub mod buffer_pool;
 pub mod bind_layout_templates;
@@ -6,3 +7,5 @@ pub mod readback;
 pub mod shader_loader;
 pub mod bind_group_builder;
 pub mod encoder_utils;
+
+pub use error::GpuError;
@@ -1,4 +1,5 @@
 // MT19937の定数
+const N: usize = 624;
 const M: usize = 397;
 const MAX_P: usize = 20; // pの最大値 まれに変わるかもしれない
 const TABLE_SIZE: usize = MAX_P + 6 + M;
@@ -17,13 +18,18 @@ const INIT_MULTIPLIER: u32 = 1812433253u32;
 
 use crate::lcg::Lcg;
 
-// テンパリング処理
-fn tempering(mut val: u32) -> u8 {
+// テンパリング処理 (32ビットそのまま)
+fn tempering_u32(mut val: u32) -> u32 {
     val ^= val >> 11;
     val ^= (val << 7) & TEMPERING_MASK_B;
     val ^= (val << 15) & TEMPERING_MASK_C;
     val ^= val >> 18;
-    ((val >> 27) & 0xFF) as u8
+    val
+}
+
+// テンパリング処理 (上位5ビット = IV)
+fn tempering(val: u32) -> u8 {
+    ((tempering_u32(val) >> 27) & 0xFF) as u8
 }
 
 // テーブルの初期化
@@ -62,11 +68,7 @@ fn generate_ivs_code(table: &[u32], p: u8) -> [u8; 6] {
 
 /// MT_1関数: seed1とpから6つの値を配列で生成
 pub fn mt_1(seed1: u64, p: u8) -> [u8; 6] {
-    let mut table = vec![0u32; TABLE_SIZE];
-    let seed_high = (seed1 >> 32) as u32;
-    
-    init_table(&mut table, seed_high, p as usize + 6 + M);
-    generate_ivs_code(&table, p)
+    mt_32((seed1 >> 32) as u32, p)
 }
 
 /// MT_0関数: seed0からseed1をLCGで生成してMT_1を呼ぶ
@@ -76,13 +78,151 @@ pub fn mt_0(seed0: u64, p: u8) -> [u8; 6] {
 }
 
 /// MT_32関数: 32ビットシードから6つの値を配列で生成
+/// p が MAX_P を超える場合は `Mt19937` で計算する
 pub fn mt_32(seed: u32, p: u8) -> [u8; 6] {
-    let mut table = vec![0u32; TABLE_SIZE];
-    
-    init_table(&mut table, seed, p as usize + 6 + M);
+    if p as usize > MAX_P {
+        return iv_sets(seed, p as u32).next().unwrap_or_default();
+    }
+    let mut table = [0u32; TABLE_SIZE];
+
+    // 最後に参照するのは table[p + 5 + M]
+    init_table(&mut table, seed, p as usize + 5 + M);
     generate_ivs_code(&table, p)
 }
 
+/**
+MT19937 本体
+
+BW/BW2 の IV は seed1 の上位32ビットで初期化した MT の出力を、
+p 回捨ててから6回分 (H,A,B,C,D,S) 取り出し、上位5ビットを使う
+*/
+#[derive(Clone)]
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0u32; N];
+        init_table(&mut state, seed, N - 1);
+        Self { state, index: N }
+    }
+
+    /// seed1 の上位32ビットで初期化する
+    pub fn from_seed1(seed1: u64) -> Self {
+        Self::new((seed1 >> 32) as u32)
+    }
+
+    /// 624個まとめて次の状態に進める
+    pub fn twist(&mut self) {
+        for i in 0..N {
+            let x = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let x_a = (x >> 1) ^ (if x & 1 != 0 { MATRIX_A } else { 0 });
+            self.state[i] = self.state[(i + M) % N] ^ x_a;
+        }
+        self.index = 0;
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+        let val = self.state[self.index];
+        self.index += 1;
+        tempering_u32(val)
+    }
+
+    /// 次の出力の上位5ビット
+    pub fn next_iv(&mut self) -> u8 {
+        (self.next_u32() >> 27) as u8
+    }
+
+    pub fn skip(&mut self, n: u32) {
+        for _ in 0..n {
+            if self.index >= N {
+                self.twist();
+            }
+            self.index += 1;
+        }
+    }
+}
+
+/**
+消費数 p, p+1, p+2, ... の IV セットを順に返すイテレータ
+
+Dream Radar や徘徊のように p が 20 を超える場合もこれを使う
+*/
+pub struct IvSets {
+    mt: Mt19937,
+    window: [u8; 6],
+}
+
+impl Iterator for IvSets {
+    type Item = [u8; 6];
+
+    fn next(&mut self) -> Option<[u8; 6]> {
+        let out = self.window;
+        self.window.rotate_left(1);
+        self.window[5] = self.mt.next_iv();
+        Some(out)
+    }
+}
+
+/// 32ビットシードで、消費数 `start` からの IV セットを返す
+pub fn iv_sets(seed: u32, start: u32) -> IvSets {
+    let mut mt = Mt19937::new(seed);
+    mt.skip(start);
+    let window = std::array::from_fn(|_| mt.next_iv());
+    IvSets { mt, window }
+}
+
+/// seed1 から、消費数 `start` からの IV セットを返す
+pub fn iv_sets_seed1(seed1: u64, start: u32) -> IvSets {
+    iv_sets((seed1 >> 32) as u32, start)
+}
+
+/**
+1つのシードについて、消費数 0..=max_step の IV セットを1回のテーブル計算でまとめて引けるようにしたもの
+
+消費数ごとに `mt_1` を呼ぶとそのたびにテーブルを作り直すので、
+複数の消費数を調べるときはこちらを使う
+*/
+pub struct IvTable {
+    values: Vec<u8>,
+}
+
+impl IvTable {
+    pub fn new(seed: u32, max_step: u32) -> Self {
+        let mut mt = Mt19937::new(seed);
+        let values = (0..max_step as usize + 6).map(|_| mt.next_iv()).collect();
+        Self { values }
+    }
+
+    pub fn from_seed1(seed1: u64, max_step: u32) -> Self {
+        Self::new((seed1 >> 32) as u32, max_step)
+    }
+
+    pub fn max_step(&self) -> u32 {
+        (self.values.len() - 6) as u32
+    }
+
+    /// 消費数 p の IV セット。範囲外なら None
+    pub fn ivs(&self, p: u32) -> Option<[u8; 6]> {
+        let p = p as usize;
+        let slice = self.values.get(p..p + 6)?;
+        slice.try_into().ok()
+    }
+
+    /// 消費数 0..=max_step の IV セットを順に返す
+    pub fn iter(&self) -> impl Iterator<Item = (u32, [u8; 6])> + '_ {
+        self.values
+            .windows(6)
+            .enumerate()
+            .map(|(p, w)| (p as u32, w.try_into().expect("window of 6")))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +231,39 @@ mod tests {
         let result = mt_0(0x9B3E7C4BC185AE31u64, 5);
         assert_eq!(result, [31, 19, 31, 31, 31, 31]);
     }
+
+    #[test]
+    fn mt19937_matches_reference_output() {
+        // 参照実装の seed=5489 の最初の出力と 10000 番目の出力
+        let mut mt = Mt19937::new(5489);
+        assert_eq!(mt.next_u32(), 3499211612);
+        mt.skip(9998);
+        assert_eq!(mt.next_u32(), 4123659995);
+    }
+
+    #[test]
+    fn iv_sets_match_mt_32_for_small_steps() {
+        for seed in [0u32, 1, 0x9B3E7C4B, 0xA90C98ED, 0xFFFF_FFFF] {
+            let table = IvTable::new(seed, MAX_P as u32);
+            for (p, ivs) in iv_sets(seed, 0).take(MAX_P + 1).enumerate() {
+                assert_eq!(ivs, mt_32(seed, p as u8), "seed={seed:08X} p={p}");
+                assert_eq!(table.ivs(p as u32), Some(ivs));
+            }
+            assert_eq!(table.ivs(MAX_P as u32 + 1), None);
+        }
+    }
+
+    #[test]
+    fn large_steps_cross_twist_boundary() {
+        let seed = 0xA90C98ED;
+        let mut mt = Mt19937::new(seed);
+        let stream: Vec<u8> = (0..1300).map(|_| mt.next_iv()).collect();
+        let table = IvTable::new(seed, 1290);
+        for p in [21u32, 100, 618, 619, 623, 624, 1247, 1290] {
+            let expected: [u8; 6] = stream[p as usize..p as usize + 6].try_into().unwrap();
+            assert_eq!(iv_sets(seed, p).next(), Some(expected), "p={p}");
+            assert_eq!(table.ivs(p), Some(expected), "p={p}");
+        }
+        assert_eq!(mt_32(seed, 200), stream[200..206]);
+    }
 }
\ No newline at end of file
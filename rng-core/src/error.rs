@@ -0,0 +1,94 @@
+use std::fmt;
+
+use crate::models::hardware::Hardware;
+
+/// 入力 (日付・性格・LCG の状態など) が不正なときのエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 存在しない日付 (年は 00-99)
+    InvalidDate { year: u8, month: u8, day: u8 },
+    /// 存在しない時刻
+    InvalidTime { hour: u8, minute: u8, second: u8 },
+    /// DS の時計が表せるのは 2000-2099 年だけ
+    YearOutOfRange(i32),
+    /// date8 / time9 の BCD が読めない
+    InvalidBcd(u32),
+    /// 日付の集合の指定が読めない ("2033-08..2035-01,!summer" のような形式)
+    InvalidDateSet(String),
+    /// 性格 ID が 0-24 の範囲外
+    InvalidNature(u8),
+    /// 性格の名前が分からない
+    UnknownNature(String),
+    /// offset_seed0 / offset_seed1 を呼ぶ前に ID を決めようとした
+    LcgNotOffset { step: u64 },
+    /// キー入力の指定が読めない ("A+START" のような形式)
+    InvalidKeys(String),
+    /// レポートの針の指定が読めない
+    InvalidNeedle(String),
+    /// 消費数の当てはめで、観測ごとのぶれの幅が上限を超えている
+    ToleranceTooLarge { tolerance: u64, max: u64 },
+    /// 本体の種類が分からない
+    UnknownHardware(String),
+    /// `hardware` と以前の `is_dslite` が食い違っている
+    HardwareConflict { hardware: Hardware, is_dslite: bool },
+    /// `hardware` も `is_dslite` もない
+    MissingHardware,
+    /// MAC アドレスが読めないか 48 ビットを超える
+    InvalidMac(String),
+    /// Timer0 がバージョンの目安の範囲から外れている
+    ImplausibleTimer0 { timer0: u16, min: u16, max: u16 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidDate { year, month, day } => {
+                write!(f, "invalid date {:02}-{:02}-{:02}", year, month, day)
+            }
+            ConfigError::InvalidTime { hour, minute, second } => {
+                write!(f, "invalid time {:02}:{:02}:{:02}", hour, minute, second)
+            }
+            ConfigError::YearOutOfRange(year) => write!(f, "year {year} is outside the DS clock range 2000-2099"),
+            ConfigError::InvalidBcd(raw) => write!(f, "invalid BCD value 0x{raw:08X}"),
+            ConfigError::InvalidDateSet(term) => write!(
+                f,
+                "invalid date term '{term}' (expected YYYY[-MM[-DD]], a '..' range, a season or a weekday)"
+            ),
+            ConfigError::InvalidNature(id) => write!(f, "invalid nature id {id} (expected 0-24)"),
+            ConfigError::UnknownNature(name) => write!(f, "unknown nature '{name}'"),
+            ConfigError::LcgNotOffset { step } => write!(
+                f,
+                "LCG state has not been advanced yet (step {step}); call offset_seed0 or offset_seed1 first"
+            ),
+            ConfigError::InvalidKeys(s) => write!(
+                f,
+                "invalid key presses '{s}' (expected keys joined by '+', e.g. 'A+START', or 'none')"
+            ),
+            ConfigError::UnknownHardware(s) => write!(
+                f,
+                "unknown hardware '{s}' (expected ds or ds_lite)"
+            ),
+            ConfigError::HardwareConflict { hardware, is_dslite } => {
+                write!(f, "hardware '{hardware}' contradicts is_dslite: {is_dslite}")
+            }
+            ConfigError::MissingHardware => write!(f, "missing field `hardware`"),
+            ConfigError::InvalidMac(s) => write!(
+                f,
+                "invalid MAC address '{s}' (expected 48 bits, e.g. 00:09:BF:6D:93:CE or 0x0009BF6D93CE)"
+            ),
+            ConfigError::ImplausibleTimer0 { timer0, min, max } => write!(
+                f,
+                "Timer0 0x{timer0:04X} is outside the usual range 0x{min:04X}-0x{max:04X} for this version"
+            ),
+            ConfigError::InvalidNeedle(s) => write!(
+                f,
+                "invalid needle '{s}' (expected digits 0-7 clockwise from up, arrows, or N/NE/E/SE/S/SW/W/NW)"
+            ),
+            ConfigError::ToleranceTooLarge { tolerance, max } => {
+                write!(f, "gap tolerance {tolerance} is larger than the limit {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
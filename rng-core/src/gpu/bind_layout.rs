@@ -1,6 +1,6 @@
-pub fn input_output_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+pub fn input_output_times_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("rng_core_input_output"),
+        label: Some("rng_core_input_output_times"),
         entries: &[
             // input
             wgpu::BindGroupLayoutEntry {
@@ -35,13 +35,24 @@ pub fn input_output_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
                 },
                 count: None,
             },
+            // time list
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     })
 }
 
-pub fn input_output_counter_params_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+pub fn input_output_counter_params_times_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("rng_core_input_output_counter_params"),
+        label: Some("rng_core_input_output_counter_params_times"),
         entries: &[
             // input
             wgpu::BindGroupLayoutEntry {
@@ -87,6 +98,17 @@ pub fn input_output_counter_params_layout(device: &wgpu::Device) -> wgpu::BindGr
                 },
                 count: None,
             },
+            // time list
+            wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     })
 }
@@ -143,9 +165,9 @@ pub fn config_output_counter_params_layout(device: &wgpu::Device) -> wgpu::BindG
     })
 }
 
-pub fn input_list_output_counter_params_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+pub fn input_list_output_counter_params_times_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("rng_core_input_list_output_counter_params"),
+        label: Some("rng_core_input_list_output_counter_params_times"),
         entries: &[
             // input
             wgpu::BindGroupLayoutEntry {
@@ -213,6 +235,17 @@ pub fn input_list_output_counter_params_layout(device: &wgpu::Device) -> wgpu::B
                 },
                 count: None,
             },
+            // time list
+            wgpu::BindGroupLayoutEntry {
+                binding: 6,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     })
 }
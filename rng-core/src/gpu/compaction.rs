@@ -0,0 +1,102 @@
+/*!
+コンパクション系カーネルのディスパッチ範囲の管理
+
+カーネルは atomicAdd のカウンタで出力位置を決め、MAX_RESULTS を超えた分は書き込まずに数だけ数える
+ホスト側はカウンタが出力バッファの容量を超えた範囲を半分に割ってディスパッチし直すので、
+どれだけ候補が多くても取りこぼしはない
+*/
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use infra::gpu::GpuError;
+
+/// これからディスパッチする範囲の作業リスト
+pub(crate) struct DispatchQueue {
+    pending: VecDeque<Range<u64>>,
+}
+
+impl DispatchQueue {
+    /// `0..total` を `max_chunk` ずつに分ける
+    pub(crate) fn new(total: u64, max_chunk: u64) -> Self {
+        let max_chunk = max_chunk.max(1);
+        let mut pending = VecDeque::new();
+        let mut base = 0;
+        while base < total {
+            let end = (base + max_chunk).min(total);
+            pending.push_back(base..end);
+            base = end;
+        }
+        Self { pending }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Range<u64>> {
+        self.pending.pop_front()
+    }
+
+    /**
+    `range` のディスパッチでカウンタが `count` になったとき、容量を超えていれば
+    範囲を半分に割って先頭に積み直して true を返す (結果は読まずに捨てる)
+
+    割った範囲はすぐ次に処理するので、結果の順序は範囲の順のまま変わらない
+    1要素の範囲は割れないので false (1スレッドの出力はキー入力の数までなので容量を超えない)
+    */
+    pub(crate) fn split_if_overflowed(&mut self, range: Range<u64>, count: usize, capacity: usize) -> bool {
+        if count <= capacity || range.end - range.start <= 1 {
+            return false;
+        }
+        let mid = range.start + (range.end - range.start) / 2;
+        self.pending.push_front(mid..range.end);
+        self.pending.push_front(range.start..mid);
+        true
+    }
+}
+
+/// 件数がユーザー指定の上限を超えていれば `GpuError::Truncated`
+pub(crate) fn check_limit(len: usize, limit: Option<usize>) -> Result<(), GpuError> {
+    match limit {
+        Some(limit) if len > limit => Err(GpuError::Truncated { limit }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各インデックスが1件ずつ候補を出すとみなして、容量 3 で全件回収できるか
+    #[test]
+    fn overflowing_ranges_are_split_in_order() {
+        let hits: Vec<u64> = vec![0, 1, 2, 3, 4, 5, 6, 9, 10, 11, 12, 13];
+        let capacity = 3;
+        let mut queue = DispatchQueue::new(16, 8);
+        let mut collected = Vec::new();
+        let mut dispatches = 0;
+        while let Some(range) = queue.pop() {
+            dispatches += 1;
+            let found: Vec<u64> = hits.iter().copied().filter(|h| range.contains(h)).collect();
+            if queue.split_if_overflowed(range, found.len(), capacity) {
+                continue;
+            }
+            assert!(found.len() <= capacity);
+            collected.extend(found);
+        }
+        assert_eq!(collected, hits);
+        assert!(dispatches > 2);
+    }
+
+    #[test]
+    fn single_element_range_is_not_split() {
+        let mut queue = DispatchQueue::new(1, 8);
+        let range = queue.pop().unwrap();
+        assert!(!queue.split_if_overflowed(range, 5, 1));
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn limit_is_inclusive() {
+        assert!(check_limit(10, Some(10)).is_ok());
+        assert!(matches!(check_limit(11, Some(10)), Err(GpuError::Truncated { limit: 10 })));
+        assert!(check_limit(usize::MAX, None).is_ok());
+    }
+}
@@ -1,25 +1,28 @@
-use crate::gpu::input_layout::{GpuInput, GpuIvConfig};
+use infra::gpu::GpuError;
+
+use crate::gpu::compaction::check_limit;
+use crate::gpu::input_layout::{next_date8, GpuInput, GpuIvConfig};
 use crate::gpu::mt_kernel;
 use crate::gpu::sha1_kernel;
 use crate::gpu::staging_layout::candidate_game_time;
 use crate::lcg::lcg_next;
 use crate::models::game_date::GameDate;
-use crate::models::{DSConfig, KeyPresses};
+use crate::models::{DSConfig, KeyPressFilter, KeyPresses, TimeSet};
 use crate::mt;
 use crate::result_base::ResultBase;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct GpuInputParams {
     nazo: [u32; 5],
     vcount_timer0_as_data5: u32,
     mac: u64,
     gxframe_xor_frame: u32,
-    hour_range: [u32; 2],
-    minute_range: [u32; 2],
-    second_range: [u32; 2],
+    times: TimeSet,
     iv_step: u32,
     iv_min: [u32; 6],
     iv_max: [u32; 6],
+    result_limit: Option<usize>,
+    key_filter: KeyPressFilter,
 }
 
 impl GpuInputParams {
@@ -41,22 +44,61 @@ impl GpuInputParams {
             vcfg.nazo_values.nazo5,
         ];
         let vcount_timer0_as_data5 = ((vcfg.vcount.0 as u32) << 16) | (ds_config.Timer0 as u32);
-        let gxframe_xor_frame = if ds_config.IsDSLite { 0x0600_0006 } else { 0x0600_0008 };
+        let gxframe_xor_frame = ds_config.Hardware.gxframe_xor_frame();
 
         Self {
             nazo,
             vcount_timer0_as_data5,
             mac: ds_config.MAC,
             gxframe_xor_frame,
-            hour_range,
-            minute_range,
-            second_range,
+            times: TimeSet::from_ranges(hour_range, minute_range, second_range),
             iv_step,
             iv_min,
             iv_max,
+            result_limit: None,
+            key_filter: KeyPressFilter::all(),
         }
     }
 
+    /**
+    1回の呼び出しで GPU から受け取る候補数の上限
+
+    超えた場合は途中までの結果を返さずに `GpuError::Truncated` にする。None なら上限なし
+    */
+    pub fn with_result_limit(mut self, limit: Option<usize>) -> Self {
+        self.result_limit = limit;
+        self
+    }
+
+    pub fn result_limit(&self) -> Option<usize> {
+        self.result_limit
+    }
+
+    /// 検索するキー入力を絞る。seed_high 版のカーネルには絞った一覧だけを渡す
+    pub fn with_key_filter(mut self, filter: KeyPressFilter) -> Self {
+        self.key_filter = filter;
+        self
+    }
+
+    pub fn key_filter(&self) -> &KeyPressFilter {
+        &self.key_filter
+    }
+
+    /// 検索する時刻を `new` の時・分・秒の範囲から差し替える (0 時をまたぐ窓や飛び飛びの時刻)
+    pub fn with_times(mut self, times: TimeSet) -> Self {
+        self.times = times;
+        self
+    }
+
+    pub fn times(&self) -> &TimeSet {
+        &self.times
+    }
+
+    /// 1日あたりに SHA-1 を計算する seed0 の数 (時刻 × 絞り込んだキー入力)
+    pub fn seeds_per_date(&self) -> u64 {
+        self.times.len() as u64 * self.key_filter.iter().count() as u64
+    }
+
     pub fn with_date(&self, date: GameDate) -> GpuInput {
         GpuInput {
             nazo: self.nazo,
@@ -64,10 +106,7 @@ impl GpuInputParams {
             mac: self.mac,
             gxframe_xor_frame: self.gxframe_xor_frame,
             date_as_data8: date.get_date8_format(),
-            hour_range: self.hour_range,
-            minute_range: self.minute_range,
-            second_range: self.second_range,
-            _pad0: 0,
+            next_date_as_data8: next_date8(date),
             iv_step: self.iv_step,
             iv_min: self.iv_min,
             iv_max: self.iv_max,
@@ -81,25 +120,28 @@ pub async fn run_sha1_mt_compact_by_dates(
     params: &GpuInputParams,
     dates: &[GameDate],
     batch_size: usize,
-) -> Result<Vec<crate::gpu::staging_layout::GpuCandidate>, wgpu::BufferAsyncError> {
+) -> Result<Vec<crate::gpu::staging_layout::GpuCandidate>, GpuError> {
     if dates.is_empty() {
         return Ok(Vec::new());
     }
 
+    let times = params.times.packed();
     let mut results = Vec::new();
     let batch = batch_size.max(1);
     let mut inputs = Vec::with_capacity(batch);
     for &date in dates {
         inputs.push(params.with_date(date));
         if inputs.len() >= batch {
-            let mut chunk = sha1_kernel::run_sha1_mt_compact(ctx, &inputs).await?;
+            let mut chunk = sha1_kernel::run_sha1_mt_compact(ctx, &inputs, &times).await?;
             results.append(&mut chunk);
+            check_limit(results.len(), params.result_limit)?;
             inputs.clear();
         }
     }
     if !inputs.is_empty() {
-        let mut chunk = sha1_kernel::run_sha1_mt_compact(ctx, &inputs).await?;
+        let mut chunk = sha1_kernel::run_sha1_mt_compact(ctx, &inputs, &times).await?;
         results.append(&mut chunk);
+        check_limit(results.len(), params.result_limit)?;
     }
 
     Ok(results)
@@ -111,9 +153,9 @@ pub async fn run_result_base_by_dates(
     params: &GpuInputParams,
     dates: &[GameDate],
     batch_size: usize,
-) -> Result<Vec<ResultBase>, wgpu::BufferAsyncError> {
+) -> Result<Vec<ResultBase>, GpuError> {
     let candidates = run_sha1_mt_compact_by_dates(ctx, params, dates, batch_size).await?;
-    Ok(build_result_base_from_candidates(ds_config, candidates, params.iv_step))
+    Ok(build_result_base_from_candidates(ds_config, candidates, params))
 }
 
 pub async fn run_result_base_seedhigh_by_dates(
@@ -122,7 +164,7 @@ pub async fn run_result_base_seedhigh_by_dates(
     params: &GpuInputParams,
     dates: &[GameDate],
     batch_size: usize,
-) -> Result<Vec<ResultBase>, wgpu::BufferAsyncError> {
+) -> Result<Vec<ResultBase>, GpuError> {
     if dates.is_empty() {
         return Ok(Vec::new());
     }
@@ -134,7 +176,9 @@ pub async fn run_result_base_seedhigh_by_dates(
         iv_max: params.iv_max,
     };
     let seed_highs = mt_kernel::run_mt_seedhigh_candidates_cached(ctx, &iv_cfg).await?;
-    if seed_highs.is_empty() {
+    let keypress_list = params.key_filter.keypress_list();
+    let times = params.times.packed();
+    if seed_highs.is_empty() || keypress_list.is_empty() || times.is_empty() {
         return Ok(Vec::new());
     }
 
@@ -144,14 +188,16 @@ pub async fn run_result_base_seedhigh_by_dates(
     for &date in dates {
         inputs.push(params.with_date(date));
         if inputs.len() >= batch {
-            let mut chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &seed_highs).await?;
-            results.append(&mut build_result_base_from_candidates(ds_config, chunk, params.iv_step));
+            let chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &times, &seed_highs, &keypress_list).await?;
+            results.append(&mut build_result_base_from_candidates(ds_config, chunk, params));
+            check_limit(results.len(), params.result_limit)?;
             inputs.clear();
         }
     }
     if !inputs.is_empty() {
-        let mut chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &seed_highs).await?;
-        results.append(&mut build_result_base_from_candidates(ds_config, chunk, params.iv_step));
+        let chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &times, &seed_highs, &keypress_list).await?;
+        results.append(&mut build_result_base_from_candidates(ds_config, chunk, params));
+        check_limit(results.len(), params.result_limit)?;
     }
 
     Ok(results)
@@ -164,7 +210,7 @@ pub async fn run_result_base_seedhigh_by_dates_multi_iv(
     dates: &[GameDate],
     batch_size: usize,
     iv_cfgs: &[GpuIvConfig],
-) -> Result<Vec<ResultBase>, wgpu::BufferAsyncError> {
+) -> Result<Vec<ResultBase>, GpuError> {
     if dates.is_empty() {
         return Ok(Vec::new());
     }
@@ -173,7 +219,9 @@ pub async fn run_result_base_seedhigh_by_dates_multi_iv(
     }
 
     let seed_highs = mt_kernel::run_mt_seedhigh_candidates_cached_multi(ctx, iv_cfgs).await?;
-    if seed_highs.is_empty() {
+    let keypress_list = params.key_filter.keypress_list();
+    let times = params.times.packed();
+    if seed_highs.is_empty() || keypress_list.is_empty() || times.is_empty() {
         return Ok(Vec::new());
     }
 
@@ -183,28 +231,32 @@ pub async fn run_result_base_seedhigh_by_dates_multi_iv(
     for &date in dates {
         inputs.push(params.with_date(date));
         if inputs.len() >= batch {
-            let mut chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &seed_highs).await?;
-            results.append(&mut build_result_base_from_candidates(ds_config, chunk, params.iv_step));
+            let chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &times, &seed_highs, &keypress_list).await?;
+            results.append(&mut build_result_base_from_candidates(ds_config, chunk, params));
+            check_limit(results.len(), params.result_limit)?;
             inputs.clear();
         }
     }
     if !inputs.is_empty() {
-        let mut chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &seed_highs).await?;
-        results.append(&mut build_result_base_from_candidates(ds_config, chunk, params.iv_step));
+        let chunk = sha1_kernel::run_sha1_seedhigh_filter(ctx, &inputs, &times, &seed_highs, &keypress_list).await?;
+        results.append(&mut build_result_base_from_candidates(ds_config, chunk, params));
+        check_limit(results.len(), params.result_limit)?;
     }
 
     Ok(results)
 }
 
+/// GPU は 0x1000 通りのキー入力を総当たりする版もあるので、ここでも絞り込む
 fn build_result_base_from_candidates(
     ds_config: DSConfig,
     candidates: Vec<crate::gpu::staging_layout::GpuCandidate>,
-    iv_step: u32,
+    params: &GpuInputParams,
 ) -> Vec<ResultBase> {
+    let iv_step = params.iv_step;
     let mut results = Vec::with_capacity(candidates.len());
     for cand in candidates {
         let raw_kp = cand.key_presses as u16;
-        if !KeyPresses::is_valid_raw(raw_kp) {
+        if !params.key_filter.matches(KeyPresses::new(raw_kp)) {
             continue;
         }
 
@@ -1,4 +1,4 @@
-use crate::models::{DSConfig, game_date::GameDate, game_date_iterator::GameDateSpec};
+use crate::models::{DSConfig, game_date::GameDate, game_date_iterator::{GameDateIterator, GameDateSpec}};
 
 use bytemuck::{Pod, Zeroable};
 
@@ -10,10 +10,8 @@ pub struct GpuInput {
     pub mac: u64,
     pub gxframe_xor_frame: u32,
     pub date_as_data8: u32,
-    pub hour_range: [u32; 2],
-    pub minute_range: [u32; 2],
-    pub second_range: [u32; 2],
-    pub _pad0: u32,
+    /// 0 時をまたいだ時刻 (時刻一覧の下位 8 ビットが 1) に使う翌日の date8。翌日がなければ 0
+    pub next_date_as_data8: u32,
     pub iv_step: u32,
     pub iv_min: [u32; 6],
     pub iv_max: [u32; 6],
@@ -29,6 +27,11 @@ pub struct GpuIvConfig {
 }
 
 
+/// 翌日の date8。2099/12/31 の翌日はないので 0
+pub(crate) fn next_date8(date: GameDate) -> u32 {
+    date.next_day().map_or(0, |next| next.get_date8_format())
+}
+
 #[cfg(test)]
 impl GpuInput {
     pub fn test_new(
@@ -37,9 +40,7 @@ impl GpuInput {
         mac: u64,
         gxframe_xor_frame: u32,
         date_as_data8: u32,
-        hour_range: [u32; 2],
-        minute_range: [u32; 2],
-        second_range: [u32; 2],
+        next_date_as_data8: u32,
         iv_step: u32,
         iv_min: [u32; 6],
         iv_max: [u32; 6],
@@ -50,10 +51,7 @@ impl GpuInput {
             mac,
             gxframe_xor_frame,
             date_as_data8,
-            hour_range,
-            minute_range,
-            second_range,
-            _pad0: 0,
+            next_date_as_data8,
             iv_step,
             iv_min,
             iv_max,
@@ -68,10 +66,11 @@ mod tests {
     use crate::models::game_date_iterator::GameDateSpec;
     use crate::models::field_range::FieldRange;
     use crate::models::game_version::GameVersion;
+    use crate::models::hardware::Hardware;
 
     #[test]
     fn test_gpu_input_iterator_next_batch() {
-        let ds_config = DSConfig::new(GameVersion::White2, 0x10F7, false, 0x0009bf6d93ce);
+        let ds_config = DSConfig::new(GameVersion::White2, 0x10F7, Hardware::DsPhat, 0x0009bf6d93ce);
         let datespec = GameDateSpec {
             year: FieldRange { min: 33, max: 33 },
             month: FieldRange { min: 8, max: 8 },
@@ -81,9 +80,6 @@ mod tests {
         let mut it = GPUInputIterator::new(
             ds_config,
             datespec,
-            [0, 23],
-            [0, 59],
-            [0, 59],
             2,
             [0; 6],
             [31; 6],
@@ -98,6 +94,55 @@ mod tests {
         let batch3 = it.next_batch(1);
         assert!(batch3.is_empty());
     }
+
+    #[test]
+    fn next_date_rolls_over_month_and_stops_after_2099() {
+        assert_eq!(next_date8(GameDate::new(33, 8, 31)), GameDate::new(33, 9, 1).get_date8_format());
+        assert_eq!(next_date8(GameDate::new(99, 12, 31)), 0);
+    }
+
+    /// WGSL 側の `GpuInput` が Rust の `#[repr(C)]` と同じ並びかを GPU なしで確かめる
+    #[test]
+    fn wgsl_gpu_input_matches_repr_c() {
+        use std::mem::offset_of;
+        use wgpu::naga::{front::wgsl, TypeInner};
+
+        let expected = [
+            ("nazo", offset_of!(GpuInput, nazo)),
+            ("vcount_timer0_as_data5", offset_of!(GpuInput, vcount_timer0_as_data5)),
+            ("mac", offset_of!(GpuInput, mac)),
+            ("gxframe_xor_frame", offset_of!(GpuInput, gxframe_xor_frame)),
+            ("date_as_data8", offset_of!(GpuInput, date_as_data8)),
+            ("next_date_as_data8", offset_of!(GpuInput, next_date_as_data8)),
+            ("iv_step", offset_of!(GpuInput, iv_step)),
+            ("iv_min", offset_of!(GpuInput, iv_min)),
+            ("iv_max", offset_of!(GpuInput, iv_max)),
+        ];
+        let kernels = [
+            ("mt.wgsl", include_str!("wgsl/mt.wgsl")),
+            ("mt_compact.wgsl", include_str!("wgsl/mt_compact.wgsl")),
+            ("sha-1.wgsl", include_str!("wgsl/sha-1.wgsl")),
+            ("sha-1_mt_compact.wgsl", include_str!("wgsl/sha-1_mt_compact.wgsl")),
+            ("sha-1_seedhigh_filter.wgsl", include_str!("wgsl/sha-1_seedhigh_filter.wgsl")),
+        ];
+        for (name, source) in kernels {
+            let module = wgsl::parse_str(source).unwrap_or_else(|e| panic!("{name}: {e}"));
+            let (_, ty) = module
+                .types
+                .iter()
+                .find(|(_, t)| t.name.as_deref() == Some("GpuInput"))
+                .unwrap_or_else(|| panic!("{name}: GpuInput not found"));
+            let TypeInner::Struct { members, span } = &ty.inner else {
+                panic!("{name}: GpuInput is not a struct");
+            };
+            let actual: Vec<(&str, usize)> = members
+                .iter()
+                .map(|m| (m.name.as_deref().unwrap_or(""), m.offset as usize))
+                .collect();
+            assert_eq!(actual, expected, "{name}");
+            assert_eq!(*span as usize, std::mem::size_of::<GpuInput>(), "{name}");
+        }
+    }
 }
 
 /**
@@ -105,26 +150,18 @@ mod tests {
  */
 pub struct GPUInputIterator {
     ds_config: DSConfig,
-    current_date: GameDate,
-    datespec: GameDateSpec,
-    hour_range: [u32; 2],
-    minute_range: [u32; 2],
-    second_range: [u32; 2],
+    dates: GameDateIterator,
     iv_step: u32,
     iv_min: [u32; 6],
     iv_max: [u32; 6],
-    finished: bool,
 }
 
 impl Iterator for GPUInputIterator {
     type Item = GpuInput;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
-        }
-
-        let out = GpuInput {
+        let date = self.dates.next()?;
+        Some(GpuInput {
             nazo: [
                 self.ds_config.get_version_config().nazo_values.nazo1,
                 self.ds_config.get_version_config().nazo_values.nazo2,
@@ -134,18 +171,13 @@ impl Iterator for GPUInputIterator {
             ],
             vcount_timer0_as_data5: ((self.ds_config.get_version_config().vcount.0 as u32) << 16) | (self.ds_config.Timer0 as u32),
             mac: self.ds_config.MAC,
-            gxframe_xor_frame: if self.ds_config.IsDSLite { 0x0600_0006} else {0x0600_0008},
-            date_as_data8: self.current_date.get_date8_format(),
-            hour_range: self.hour_range,
-            minute_range: self.minute_range,
-            second_range: self.second_range,
-            _pad0: 0,
+            gxframe_xor_frame: self.ds_config.Hardware.gxframe_xor_frame(),
+            date_as_data8: date.get_date8_format(),
+            next_date_as_data8: next_date8(date),
             iv_step: self.iv_step,
             iv_min: self.iv_min,
             iv_max: self.iv_max,
-        };
-        self.advance();
-        Some(out)
+        })
     }
 }
 
@@ -153,24 +185,16 @@ impl GPUInputIterator {
     pub fn new(
         ds_config: DSConfig,
         datespec: GameDateSpec,
-        hour_range: [u32; 2],
-        minute_range: [u32; 2],
-        second_range: [u32; 2],
         iv_step: u32,
         iv_min: [u32; 6],
         iv_max: [u32; 6],
     ) -> Self {
         Self {
             ds_config,
-            current_date: datespec.start(),
-            datespec,
-            hour_range,
-            minute_range,
-            second_range,
+            dates: GameDateIterator::new(datespec),
             iv_step,
             iv_min,
             iv_max,
-            finished: false,
         }
     }
 
@@ -184,31 +208,4 @@ impl GPUInputIterator {
         }
         out
     }
-
-    #[inline]
-    fn advance(&mut self) {
-        // 日
-        self.current_date.day += 1;
-        let dim = self.current_date.days_in_month();
-        if self.current_date.day <= dim && self.datespec.day.contains(self.current_date.day) {
-            return;
-        }
-        self.current_date.day = self.datespec.day.min;
-
-        // 月
-        self.current_date.month += 1;
-        if self.current_date.month <= self.datespec.month.max {
-            return;
-        }
-        self.current_date.month = self.datespec.month.min;
-
-        // 年
-        self.current_date.year += 1;
-        if self.current_date.year <= self.datespec.year.max {
-            return;
-        }
-
-        // 完全終了
-        self.finished = true;
-    }
 }
@@ -4,4 +4,8 @@ pub mod staging_layout;
 pub mod bind_layout;
 pub mod sha1_kernel;
 pub mod mt_kernel;
+pub mod seed_high_cache;
+#[cfg(test)]
+mod sha1_codegen;
+mod compaction;
 pub mod helpers;
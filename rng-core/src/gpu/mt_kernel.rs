@@ -5,13 +5,16 @@ use infra::gpu::encoder_utils::EncoderUtils;
 use infra::gpu::pipeline_factory::PipelineFactory;
 use infra::gpu::readback::Readback;
 use infra::gpu::shader_loader::ShaderLoader;
+use infra::gpu::GpuError;
 use wgpu::util::DeviceExt;
 use std::collections::HashMap;
 use std::sync::{Mutex, OnceLock};
 
 use crate::gpu::bind_layout::{candidate_config_output_layout, candidate_config_output_counter_layout, config_output_counter_params_layout};
+use crate::gpu::compaction::DispatchQueue;
 use crate::gpu::input_layout::{GpuInput, GpuIvConfig};
 use crate::gpu::local_gpu_config::GpuKernelConfig;
+use crate::gpu::seed_high_cache::{SeedHighDiskCache, SeedHighKey, SeedHighSet};
 use crate::gpu::staging_layout::GpuCandidate;
 
 pub const MAX_RESULTS: usize = 1 << 20;
@@ -23,20 +26,13 @@ struct DispatchParams {
     total_len: u64,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct IvKey {
-    iv_step: u32,
-    iv_min: [u32; 6],
-    iv_max: [u32; 6],
-}
-
-static SEED_HIGH_CACHE: OnceLock<Mutex<HashMap<IvKey, Vec<u32>>>> = OnceLock::new();
+static SEED_HIGH_CACHE: OnceLock<Mutex<HashMap<SeedHighKey, SeedHighSet>>> = OnceLock::new();
 
 pub async fn run_mt(
     ctx: &infra::gpu::context::GpuContext,
     candidates: &[GpuCandidate],
     configs: &[GpuInput],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+) -> Result<Vec<GpuCandidate>, GpuError> {
     assert!(
         configs.len() == 1 || configs.len() == candidates.len(),
         "configs must have length 1 or match candidates length"
@@ -91,26 +87,52 @@ pub async fn run_mt(
     ctx.queue.submit(Some(encoder.finish()));
 
     let readback = Readback::new(ctx);
-    readback
+    Ok(readback
         .read_buffer_with_pool::<GpuCandidate>(
             &pool,
             &output_buffer,
             output_len,
             Some("rng_core_mt_readback"),
         )
-        .await
+        .await?)
 }
 
+/// 出力バッファがあふれた場合は候補を半分ずつに分けて数え直すので、結果は欠けない
 pub async fn run_mt_compact(
     ctx: &infra::gpu::context::GpuContext,
     candidates: &[GpuCandidate],
     configs: &[GpuInput],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+) -> Result<Vec<GpuCandidate>, GpuError> {
     assert!(
         configs.len() == 1 || configs.len() == candidates.len(),
         "configs must have length 1 or match candidates length"
     );
 
+    let mut results = Vec::new();
+    let total = candidates.len() as u64;
+    let mut queue = DispatchQueue::new(total, total);
+    while let Some(range) = queue.pop() {
+        let (start, end) = (range.start as usize, range.end as usize);
+        let cfgs = if configs.len() == 1 { configs } else { &configs[start..end] };
+        let (count, mut chunk) = run_mt_compact_once(ctx, &candidates[start..end], cfgs).await?;
+        if queue.split_if_overflowed(range, count, MAX_RESULTS) {
+            continue;
+        }
+        results.append(&mut chunk);
+    }
+    Ok(results)
+}
+
+/// 1回だけディスパッチして (カウンタの値, 読めた分) を返す
+async fn run_mt_compact_once(
+    ctx: &infra::gpu::context::GpuContext,
+    candidates: &[GpuCandidate],
+    configs: &[GpuInput],
+) -> Result<(usize, Vec<GpuCandidate>), GpuError> {
+    if candidates.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+
     let shader = ShaderLoader::from_wgsl(
         &ctx.device,
         Some("rng_core_mt_compact"),
@@ -170,25 +192,33 @@ pub async fn run_mt_compact(
         .read_buffer::<u32>(&counter_buffer, 1, Some("rng_core_mt_count_readback"))
         .await?;
     let count = count_vec[0] as usize;
-    if count == 0 {
-        return Ok(Vec::new());
+    if count == 0 || count > MAX_RESULTS {
+        return Ok((count, Vec::new()));
     }
 
-    let read_len = count.min(MAX_RESULTS);
-    readback
+    let results = readback
         .read_buffer_with_pool::<GpuCandidate>(
             &pool,
             &output_buffer,
-            read_len,
+            count,
             Some("rng_core_mt_readback"),
         )
-        .await
+        .await?;
+    Ok((count, results))
 }
 
 pub async fn run_mt_seedhigh_candidates(
     ctx: &infra::gpu::context::GpuContext,
     config: &GpuIvConfig,
-) -> Result<Vec<u32>, wgpu::BufferAsyncError> {
+) -> Result<Vec<u32>, GpuError> {
+    Ok(run_mt_seedhigh_candidate_set(ctx, config).await?.seeds)
+}
+
+/// 2^32 通りを総当たりする。出力バッファがあふれた範囲は割ってやり直すので、結果は常に全件
+pub async fn run_mt_seedhigh_candidate_set(
+    ctx: &infra::gpu::context::GpuContext,
+    config: &GpuIvConfig,
+) -> Result<SeedHighSet, GpuError> {
     let shader = ShaderLoader::from_wgsl(
         &ctx.device,
         Some("rng_core_mt_seedhigh_compact"),
@@ -213,7 +243,6 @@ pub async fn run_mt_seedhigh_candidates(
 
     let mut results = Vec::new();
     let total_len = (u32::MAX as u64) + 1;
-    let mut base: u64 = 0;
     let wg = GpuKernelConfig::SHA1_MT.workgroup_size as u64;
     let max_groups = ctx
         .device
@@ -221,9 +250,10 @@ pub async fn run_mt_seedhigh_candidates(
         .max_compute_workgroups_per_dimension
         .min(65535) as u64;
     let max_dispatch = max_groups.saturating_mul(wg);
-    while base < total_len {
-        let remaining = total_len - base;
-        let chunk_len = remaining.min(max_dispatch) as u32;
+    let mut queue = DispatchQueue::new(total_len, max_dispatch);
+    while let Some(range) = queue.pop() {
+        let base = range.start;
+        let chunk_len = (range.end - range.start) as u32;
 
         let counter_buffer = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("rng_core_mt_seedhigh_counter_buffer"),
@@ -268,51 +298,68 @@ pub async fn run_mt_seedhigh_candidates(
             .read_buffer::<u32>(&counter_buffer, 1, Some("rng_core_mt_seedhigh_count"))
             .await?;
         let count = count_vec[0] as usize;
+        if queue.split_if_overflowed(range, count, MAX_RESULTS) {
+            continue;
+        }
         if count > 0 {
-            let read_len = count.min(MAX_RESULTS);
             let mut chunk = readback
                 .read_buffer_with_pool::<u32>(
                     &pool,
                     &output_buffer,
-                    read_len,
+                    count.min(MAX_RESULTS),
                     Some("rng_core_mt_seedhigh_readback"),
                 )
                 .await?;
             results.append(&mut chunk);
-            if results.len() >= MAX_RESULTS {
-                results.truncate(MAX_RESULTS);
-                break;
-            }
         }
-
-        base += chunk_len as u64;
     }
 
-    Ok(results)
+    Ok(SeedHighSet { seeds: results, truncated: false })
 }
 
 pub async fn run_mt_seedhigh_candidates_cached(
     ctx: &infra::gpu::context::GpuContext,
     config: &GpuIvConfig,
-) -> Result<Vec<u32>, wgpu::BufferAsyncError> {
-    let key = IvKey {
-        iv_step: config.iv_step,
-        iv_min: config.iv_min,
-        iv_max: config.iv_max,
-    };
+) -> Result<Vec<u32>, GpuError> {
+    Ok(run_mt_seedhigh_candidate_set_cached(ctx, config).await?.seeds)
+}
+
+/**
+プロセス内のキャッシュ → ディスクキャッシュ → GPU の順に探す
+
+ディスクキャッシュが壊れていたり書き込めなかったりした場合は、
+GPU で計算し直すだけでエラーにはしない
+*/
+pub async fn run_mt_seedhigh_candidate_set_cached(
+    ctx: &infra::gpu::context::GpuContext,
+    config: &GpuIvConfig,
+) -> Result<SeedHighSet, GpuError> {
+    let key = SeedHighKey::from_config(config);
 
     if let Some(cache) = SEED_HIGH_CACHE.get() {
         if let Ok(map) = cache.lock() {
             if let Some(existing) = map.get(&key) {
-                //println!("seed_high cache hit (len={})", existing.len());
                 return Ok(existing.clone());
             }
         }
     }
 
-    //println!("seed_high cache miss; computing");
-    let computed = run_mt_seedhigh_candidates(ctx, config).await?;
-    //println!("seed_high computed len={}", computed.len());
+    let disk = SeedHighDiskCache::default_location();
+    // 以前の版が打ち切って保存したものは欠けているので使わない
+    let loaded = disk
+        .as_ref()
+        .and_then(|d| d.load(&key).ok().flatten())
+        .filter(|set| !set.truncated);
+    let computed = match loaded {
+        Some(set) => set,
+        None => {
+            let set = run_mt_seedhigh_candidate_set(ctx, config).await?;
+            if let Some(d) = disk.as_ref() {
+                let _ = d.store(&key, &set);
+            }
+            set
+        }
+    };
 
     let cache = SEED_HIGH_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
     if let Ok(mut map) = cache.lock() {
@@ -325,7 +372,7 @@ pub async fn run_mt_seedhigh_candidates_cached(
 pub async fn run_mt_seedhigh_candidates_cached_multi(
     ctx: &infra::gpu::context::GpuContext,
     configs: &[GpuIvConfig],
-) -> Result<Vec<u32>, wgpu::BufferAsyncError> {
+) -> Result<Vec<u32>, GpuError> {
     if configs.is_empty() {
         return Ok(Vec::new());
     }
@@ -353,7 +400,7 @@ mod tests {
     #[test]
     fn test_mt_seedhigh_speed() {
         pollster::block_on(async {
-            let ctx = GpuContext::new().await;
+            let ctx = GpuContext::new().await.expect("GPU context");
 
             let iv_min: [u32; 6] = [31u32, 31u32, 31u32, 8, 31u32, 31u32];
             let iv_max: [u32; 6] = [31u32, 31u32, 31u32, 31, 31u32, 31u32];
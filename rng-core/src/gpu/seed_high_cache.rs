@@ -0,0 +1,320 @@
+/*!
+seed_high 候補のディスクキャッシュ
+
+`run_mt_seedhigh_candidates` の 2^32 通りの総当たりは IV 条件が同じなら毎回同じ結果になるので、
+(iv_step, iv_min, iv_max) ごとにファイルに保存して次回の実行で使い回す
+
+ファイル形式 (リトルエンディアン)
+- 0..8   マジック `SGSEEDHI`
+- 8..12  バージョン (u32)
+- 12..16 フラグ (u32)。bit0 = MAX_RESULTS で打ち切られた (以前の版のみ)
+- 16..20 iv_step (u32)
+- 20..26 iv_min (u8 × 6)
+- 26..32 iv_max (u8 × 6)
+- 32..40 件数 (u64)
+- 40..48 本体のチェックサム (FNV-1a 64)
+- 48..   seed_high (u32) × 件数
+*/
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::gpu::input_layout::GpuIvConfig;
+
+const MAGIC: [u8; 8] = *b"SGSEEDHI";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 48;
+const FLAG_TRUNCATED: u32 = 1;
+const EXTENSION: &str = "seedhigh";
+
+/// キャッシュのキー
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SeedHighKey {
+    pub iv_step: u32,
+    pub iv_min: [u8; 6],
+    pub iv_max: [u8; 6],
+}
+
+impl SeedHighKey {
+    pub fn from_config(config: &GpuIvConfig) -> Self {
+        Self {
+            iv_step: config.iv_step,
+            iv_min: config.iv_min.map(|v| v.min(31) as u8),
+            iv_max: config.iv_max.map(|v| v.min(31) as u8),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        let hex = |ivs: &[u8; 6]| ivs.iter().map(|v| format!("{v:02x}")).collect::<String>();
+        format!("step{}_{}_{}.{}", self.iv_step, hex(&self.iv_min), hex(&self.iv_max), EXTENSION)
+    }
+}
+
+/// seed_high 候補の集合
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SeedHighSet {
+    pub seeds: Vec<u32>,
+    /// 以前の版で GPU の出力バッファ (MAX_RESULTS) があふれて一部が欠けている。今の版では立たない
+    pub truncated: bool,
+}
+
+/// `list` で返すキャッシュの1件
+#[derive(Clone, Debug)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub key: SeedHighKey,
+    pub count: u64,
+    pub truncated: bool,
+    pub bytes: u64,
+}
+
+pub struct SeedHighDiskCache {
+    dir: PathBuf,
+}
+
+impl SeedHighDiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /**
+    既定の場所
+    1. `SUGARTOOLS_CACHE_DIR`
+    2. `XDG_CACHE_HOME/sugartools`
+    3. `HOME/.cache/sugartools` (Windows は `LOCALAPPDATA/sugartools`)
+
+    どれも取れなければ None (ディスクキャッシュは使わない)
+    */
+    pub fn default_location() -> Option<Self> {
+        let env_dir = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        let base = if let Some(dir) = env_dir("SUGARTOOLS_CACHE_DIR") {
+            dir
+        } else if let Some(dir) = env_dir("XDG_CACHE_HOME") {
+            dir.join("sugartools")
+        } else if let Some(dir) = env_dir("LOCALAPPDATA") {
+            dir.join("sugartools")
+        } else {
+            env_dir("HOME")?.join(".cache").join("sugartools")
+        };
+        Some(Self::new(base.join("seed_high")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// キャッシュになければ Ok(None)。壊れていれば InvalidData
+    pub fn load(&self, key: &SeedHighKey) -> io::Result<Option<SeedHighSet>> {
+        let path = self.dir.join(key.file_name());
+        let bytes = match fs::read(&path) {
+            Ok(b) => b,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let (file_key, set) = decode(&bytes)?;
+        if file_key != *key {
+            return Err(invalid_data("seed_high cache key does not match its file name"));
+        }
+        Ok(Some(set))
+    }
+
+    /// 一時ファイルに書いてから rename するので、途中で落ちても壊れたファイルは残らない
+    pub fn store(&self, key: &SeedHighKey, set: &SeedHighSet) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(key.file_name());
+        let tmp = path.with_extension(format!("{EXTENSION}.tmp{}", std::process::id()));
+        {
+            let mut w = BufWriter::new(File::create(&tmp)?);
+            encode(&mut w, key, set)?;
+            w.flush()?;
+        }
+        fs::rename(&tmp, &path)
+    }
+
+    pub fn list(&self) -> io::Result<Vec<CacheEntry>> {
+        let mut out = Vec::new();
+        for path in self.cache_files()? {
+            let bytes = fs::read(&path)?;
+            let Ok((key, set)) = decode(&bytes) else {
+                continue;
+            };
+            out.push(CacheEntry {
+                path,
+                key,
+                count: set.seeds.len() as u64,
+                truncated: set.truncated,
+                bytes: bytes.len() as u64,
+            });
+        }
+        out.sort_by_key(|e| (e.key.iv_step, e.key.iv_min, e.key.iv_max));
+        Ok(out)
+    }
+
+    /// 消したファイル数を返す
+    pub fn clear(&self) -> io::Result<usize> {
+        let files = self.cache_files()?;
+        for path in &files {
+            fs::remove_file(path)?;
+        }
+        Ok(files.len())
+    }
+
+    fn cache_files(&self) -> io::Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(e) => e,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == EXTENSION) {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn encode(w: &mut impl Write, key: &SeedHighKey, set: &SeedHighSet) -> io::Result<()> {
+    let payload: &[u8] = bytemuck::cast_slice(&set.seeds);
+    let flags = if set.truncated { FLAG_TRUNCATED } else { 0 };
+    w.write_all(&MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    w.write_all(&flags.to_le_bytes())?;
+    w.write_all(&key.iv_step.to_le_bytes())?;
+    w.write_all(&key.iv_min)?;
+    w.write_all(&key.iv_max)?;
+    w.write_all(&(set.seeds.len() as u64).to_le_bytes())?;
+    w.write_all(&fnv1a64(payload).to_le_bytes())?;
+    if cfg!(target_endian = "little") {
+        w.write_all(payload)
+    } else {
+        set.seeds.iter().try_for_each(|s| w.write_all(&s.to_le_bytes()))
+    }
+}
+
+fn decode(bytes: &[u8]) -> io::Result<(SeedHighKey, SeedHighSet)> {
+    if bytes.len() < HEADER_LEN || bytes[0..8] != MAGIC {
+        return Err(invalid_data("not a seed_high cache file"));
+    }
+    let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+
+    let version = u32_at(8);
+    if version != VERSION {
+        return Err(invalid_data(format!("unsupported seed_high cache version {version}")));
+    }
+    let flags = u32_at(12);
+    let key = SeedHighKey {
+        iv_step: u32_at(16),
+        iv_min: bytes[20..26].try_into().unwrap(),
+        iv_max: bytes[26..32].try_into().unwrap(),
+    };
+    let count = u64_at(32);
+    let checksum = u64_at(40);
+
+    let payload = &bytes[HEADER_LEN..];
+    if count.checked_mul(4).is_none_or(|len| payload.len() as u64 != len) {
+        return Err(invalid_data("seed_high cache file is truncated"));
+    }
+    if fnv1a64(payload) != checksum {
+        return Err(invalid_data("seed_high cache checksum mismatch"));
+    }
+    let seeds = payload
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    Ok((key, SeedHighSet { seeds, truncated: flags & FLAG_TRUNCATED != 0 }))
+}
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> SeedHighDiskCache {
+        let dir = std::env::temp_dir().join(format!("rng_core_seedhigh_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        SeedHighDiskCache::new(dir)
+    }
+
+    fn key() -> SeedHighKey {
+        SeedHighKey {
+            iv_step: 16,
+            iv_min: [27, 29, 29, 29, 0, 25],
+            iv_max: [31, 31, 31, 31, 31, 25],
+        }
+    }
+
+    #[test]
+    fn store_load_list_clear() {
+        let cache = temp_cache("roundtrip");
+        assert_eq!(cache.load(&key()).unwrap(), None);
+
+        let set = SeedHighSet { seeds: vec![1, 0x9B3E7C4B, u32::MAX], truncated: true };
+        cache.store(&key(), &set).unwrap();
+        assert_eq!(cache.load(&key()).unwrap(), Some(set));
+
+        let entries = cache.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, key());
+        assert_eq!(entries[0].count, 3);
+        assert!(entries[0].truncated);
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.load(&key()).unwrap(), None);
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn corrupt_file_is_rejected() {
+        let cache = temp_cache("corrupt");
+        let set = SeedHighSet { seeds: vec![5, 6, 7], truncated: false };
+        cache.store(&key(), &set).unwrap();
+
+        let path = cache.dir().join(key().file_name());
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, &bytes).unwrap();
+
+        let err = cache.load(&key()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn overflowing_count_is_rejected() {
+        let cache = temp_cache("overflow");
+        let set = SeedHighSet { seeds: vec![5, 6, 7], truncated: false };
+        cache.store(&key(), &set).unwrap();
+
+        // count * 4 が u64 で桁あふれして payload 長と一致してしまう値
+        let path = cache.dir().join(key().file_name());
+        let mut bytes = fs::read(&path).unwrap();
+        let count = (1u64 << 62) + 3;
+        bytes[32..40].copy_from_slice(&count.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        let err = cache.load(&key()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn key_from_config_matches_file_name() {
+        let cfg = GpuIvConfig { iv_step: 17, _pad0: 0, iv_min: [0; 6], iv_max: [31; 6] };
+        let key = SeedHighKey::from_config(&cfg);
+        assert_eq!(key.file_name(), "step17_000000000000_1f1f1f1f1f1f.seedhigh");
+    }
+}
@@ -0,0 +1,130 @@
+/*!
+`sha-1_seedhigh_filter.wgsl` の展開済み SHA-1 部分の生成
+
+CPU 側の `Seed0Prefix` と同じく、ラウンド 0-11 と W[16..20] は日付・時刻ごとに一度だけ、
+ラウンド 12-79 と W[20..80] はキー入力ごとに計算する。境界は `sha_1` の定数から取るので、
+CPU とカーネルで分け方がずれることはない
+
+カーネル中の `// BEGIN GENERATED <name>` と `// END GENERATED <name>` の間がここの出力と一致するかをテストで確かめる
+`SUGARTOOLS_UPDATE_WGSL=1 cargo test -p rng-core sha1_codegen` で書き換えられる
+*/
+
+use std::fmt::Write;
+
+use crate::sha_1::{round_k, KEYPRESS_ROUND, KEY_INDEPENDENT_SCHEDULE};
+
+const KERNEL_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/gpu/wgsl/sha-1_seedhigh_filter.wgsl");
+
+fn round_f(t: usize) -> &'static str {
+    match t {
+        0..=19 => "((b & c) | ((~b) & d))",
+        20..=39 | 60..=79 => "(b ^ c ^ d)",
+        _ => "((b & c) | (b & d) | (c & d))",
+    }
+}
+
+fn emit_schedule(out: &mut String, indent: &str, range: std::ops::Range<usize>) {
+    for t in range {
+        writeln!(
+            out,
+            "{indent}w[{t}u] = rotl32(w[{}u] ^ w[{}u] ^ w[{}u] ^ w[{}u], 1u);",
+            t - 3,
+            t - 8,
+            t - 14,
+            t - 16
+        )
+        .unwrap();
+    }
+}
+
+fn emit_rounds(out: &mut String, indent: &str, range: std::ops::Range<usize>) {
+    for t in range {
+        writeln!(out, "{indent}// t={t}").unwrap();
+        writeln!(
+            out,
+            "{indent}let temp_{t} = rotl32(a, 5u) + {} + e + 0x{:08X}u + w[{t}u];",
+            round_f(t),
+            round_k(t)
+        )
+        .unwrap();
+        for line in ["e = d;", "d = c;", "c = rotl32(b, 30u);", "b = a;"] {
+            writeln!(out, "{indent}{line}").unwrap();
+        }
+        writeln!(out, "{indent}a = temp_{t};").unwrap();
+    }
+}
+
+/// 日付・時刻ごとの部分 (W[16..20] とラウンド 0-11)
+fn prefix_section() -> String {
+    let mut out = String::new();
+    emit_schedule(&mut out, "    ", 16..KEY_INDEPENDENT_SCHEDULE);
+    out.push('\n');
+    emit_rounds(&mut out, "    ", 0..KEYPRESS_ROUND);
+    out
+}
+
+/// キー入力ごとの部分 (W[20..80] とラウンド 12-79)
+fn keypress_section() -> String {
+    let mut out = String::new();
+    emit_schedule(&mut out, "        ", KEY_INDEPENDENT_SCHEDULE..80);
+    out.push('\n');
+    emit_rounds(&mut out, "        ", KEYPRESS_ROUND..80);
+    out
+}
+
+/// `source` の `name` の区間を `body` に差し替える。マーカーがなければ None
+fn replace_section(source: &str, name: &str, body: &str) -> Option<String> {
+    let begin = format!("// BEGIN GENERATED {name}\n");
+    let end_marker = format!("// END GENERATED {name}");
+    let start = source.find(&begin)? + begin.len();
+    let end_line = source[start..].find(&end_marker)? + start;
+    // END マーカーの行頭 (インデント) は残す
+    let end = source[..end_line].rfind('\n').map_or(end_line, |i| i + 1);
+    Some(format!("{}{}{}", &source[..start], body, &source[end..]))
+}
+
+fn generate(source: &str) -> String {
+    let with_prefix = replace_section(source, "prefix", &prefix_section()).expect("prefix markers");
+    replace_section(&with_prefix, "keypress", &keypress_section()).expect("keypress markers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seedhigh_filter_kernel_is_up_to_date() {
+        let current = std::fs::read_to_string(KERNEL_PATH).unwrap();
+        let expected = generate(&current);
+        if std::env::var_os("SUGARTOOLS_UPDATE_WGSL").is_some() {
+            std::fs::write(KERNEL_PATH, &expected).unwrap();
+            return;
+        }
+        assert!(
+            current == expected,
+            "sha-1_seedhigh_filter.wgsl is stale; rerun with SUGARTOOLS_UPDATE_WGSL=1"
+        );
+    }
+
+    /// GPU がなくても、生成したカーネルが WGSL として正しいことは確かめられる
+    #[test]
+    fn seedhigh_filter_kernel_validates() {
+        use wgpu::naga::valid::{Capabilities, ValidationFlags, Validator};
+
+        let module = wgpu::naga::front::wgsl::parse_str(include_str!("wgsl/sha-1_seedhigh_filter.wgsl"))
+            .expect("WGSL parse");
+        Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .expect("WGSL validation");
+    }
+
+    #[test]
+    fn replace_section_keeps_surroundings() {
+        let src = "head\n    // BEGIN GENERATED x\n    old\n    // END GENERATED x\ntail\n";
+        assert_eq!(
+            replace_section(src, "x", "    new\n").unwrap(),
+            "head\n    // BEGIN GENERATED x\n    new\n    // END GENERATED x\ntail\n"
+        );
+        assert!(replace_section(src, "y", "").is_none());
+    }
+}
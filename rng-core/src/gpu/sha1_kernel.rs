@@ -4,14 +4,16 @@ use infra::gpu::encoder_utils::EncoderUtils;
 use infra::gpu::pipeline_factory::PipelineFactory;
 use infra::gpu::readback::Readback;
 use infra::gpu::shader_loader::ShaderLoader;
+use infra::gpu::GpuError;
 use wgpu::util::DeviceExt;
 
-use crate::gpu::bind_layout::{input_output_layout, input_output_counter_params_layout, input_list_output_counter_params_layout};
+use crate::gpu::compaction::DispatchQueue;
+use crate::gpu::bind_layout::{input_output_times_layout, input_output_counter_params_times_layout, input_list_output_counter_params_times_layout};
 use crate::gpu::input_layout::{GpuInput, GpuIvConfig, GPUInputIterator};
 use crate::gpu::staging_layout::GpuCandidate;
 use crate::gpu::local_gpu_config::GpuKernelConfig;
 use crate::models::game_date_iterator::GameDateSpec;
-use crate::models::{DSConfig, KeyPresses};
+use crate::models::{DSConfig, KeyPresses, TimeSet};
 use crate::gpu::mt_kernel;
 
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -35,33 +37,19 @@ struct ListDispatchParams {
 pub async fn run_sha1(
     ctx: &infra::gpu::context::GpuContext,
     input: &[GpuInput],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+    times: &[u32],
+) -> Result<Vec<GpuCandidate>, GpuError> {
 
     if input.is_empty() {
         return Ok(Vec::new());
     }
 
-    let first = &input[0];
-    if first.hour_range[1] < first.hour_range[0]
-        || first.minute_range[1] < first.minute_range[0]
-        || first.second_range[1] < first.second_range[0] {
+    if times.is_empty() {
         return Ok(Vec::new());
     }
 
-    #[cfg(debug_assertions)]
-    {
-        for other in input.iter().skip(1) {
-            assert_eq!(other.hour_range, first.hour_range, "hour_range must match across inputs");
-            assert_eq!(other.minute_range, first.minute_range, "minute_range must match across inputs");
-            assert_eq!(other.second_range, first.second_range, "second_range must match across inputs");
-        }
-    }
-
     let kp_count = 0x1000usize;
-    let h_count = (first.hour_range[1] - first.hour_range[0] + 1) as usize;
-    let m_count = (first.minute_range[1] - first.minute_range[0] + 1) as usize;
-    let s_count = (first.second_range[1] - first.second_range[0] + 1) as usize;
-    let time_count = h_count * m_count * s_count;
+    let time_count = times.len();
     let output_len_u64 = (kp_count as u64)
         * (time_count as u64)
         * (input.len() as u64);
@@ -83,8 +71,11 @@ pub async fn run_sha1(
     let input_buffer = pool
         .create_init(input, BufferKind::Input, "rng_core_sha1_expand_input_buffer")
         .buffer;
+    let time_buffer = pool
+        .create_init(times, BufferKind::Input, "rng_core_sha1_expand_time_buffer")
+        .buffer;
 
-    let layout = input_output_layout(&ctx.device);
+    let layout = input_output_times_layout(&ctx.device);
     let pipeline = PipelineFactory::new(&ctx.device)
         .create_compute(&shader, &layout, "main");
 
@@ -121,6 +112,7 @@ pub async fn run_sha1(
             .buffer(0, &input_buffer)
             .buffer(1, &output_buffer)
             .buffer(2, &params_buffer)
+            .buffer(3, &time_buffer)
             .build(&ctx.device, Some("rng_core_sha1_expand_bind_group"));
 
         let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -159,29 +151,19 @@ pub async fn run_sha1(
 pub async fn run_sha1_mt(
     ctx: &infra::gpu::context::GpuContext,
     input: &[GpuInput],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+    times: &[u32],
+) -> Result<Vec<GpuCandidate>, GpuError> {
     if input.is_empty() {
         return Ok(Vec::new());
     }
 
-    let first = &input[0];
-    if first.hour_range[1] < first.hour_range[0]
-        || first.minute_range[1] < first.minute_range[0]
-        || first.second_range[1] < first.second_range[0] {
+    if times.is_empty() {
         return Ok(Vec::new());
     }
 
     #[cfg(debug_assertions)]
     {
-        for other in input.iter().skip(1) {
-            assert_eq!(other.hour_range, first.hour_range, "hour_range must match across inputs");
-            assert_eq!(other.minute_range, first.minute_range, "minute_range must match across inputs");
-            assert_eq!(other.second_range, first.second_range, "second_range must match across inputs");
-        }
-    }
-
-    #[cfg(debug_assertions)]
-    {
+        let first = &input[0];
         for other in input.iter().skip(1) {
             assert_eq!(other.iv_step, first.iv_step, "iv_step must match across inputs");
             assert_eq!(other.iv_min, first.iv_min, "iv_min must match across inputs");
@@ -190,10 +172,7 @@ pub async fn run_sha1_mt(
     }
 
     let kp_count = 0x1000usize;
-    let h_count = (first.hour_range[1] - first.hour_range[0] + 1) as usize;
-    let m_count = (first.minute_range[1] - first.minute_range[0] + 1) as usize;
-    let s_count = (first.second_range[1] - first.second_range[0] + 1) as usize;
-    let time_count = h_count * m_count * s_count;
+    let time_count = times.len();
     let output_len_u64 = (kp_count as u64)
         * (time_count as u64)
         * (input.len() as u64);
@@ -215,8 +194,11 @@ pub async fn run_sha1_mt(
     let input_buffer = pool
         .create_init(input, BufferKind::Input, "rng_core_sha1_expand_input_buffer")
         .buffer;
+    let time_buffer = pool
+        .create_init(times, BufferKind::Input, "rng_core_sha1_expand_time_buffer")
+        .buffer;
 
-    let layout = input_output_layout(&ctx.device);
+    let layout = input_output_times_layout(&ctx.device);
     let pipeline = PipelineFactory::new(&ctx.device)
         .create_compute(&shader, &layout, "main");
 
@@ -253,6 +235,7 @@ pub async fn run_sha1_mt(
             .buffer(0, &input_buffer)
             .buffer(1, &output_buffer)
             .buffer(2, &params_buffer)
+            .buffer(3, &time_buffer)
             .build(&ctx.device, Some("rng_core_sha1_expand_bind_group"));
 
         let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -285,10 +268,6 @@ pub async fn run_sha1_mt(
         // MT uses only IV config, which is expected to be the same across inputs.
         let mut mt_chunk = crate::gpu::mt_kernel::run_mt_compact(ctx, &sha1_chunk, &input[0..1]).await?;
         results.append(&mut mt_chunk);
-        if results.len() >= crate::gpu::mt_kernel::MAX_RESULTS {
-            results.truncate(crate::gpu::mt_kernel::MAX_RESULTS);
-            break;
-        }
         base += chunk_len;
     }
 
@@ -298,24 +277,20 @@ pub async fn run_sha1_mt(
 pub async fn run_sha1_mt_compact(
     ctx: &infra::gpu::context::GpuContext,
     input: &[GpuInput],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+    times: &[u32],
+) -> Result<Vec<GpuCandidate>, GpuError> {
     if input.is_empty() {
         return Ok(Vec::new());
     }
 
-    let first = &input[0];
-    if first.hour_range[1] < first.hour_range[0]
-        || first.minute_range[1] < first.minute_range[0]
-        || first.second_range[1] < first.second_range[0] {
+    if times.is_empty() {
         return Ok(Vec::new());
     }
 
     #[cfg(debug_assertions)]
     {
+        let first = &input[0];
         for other in input.iter().skip(1) {
-            assert_eq!(other.hour_range, first.hour_range, "hour_range must match across inputs");
-            assert_eq!(other.minute_range, first.minute_range, "minute_range must match across inputs");
-            assert_eq!(other.second_range, first.second_range, "second_range must match across inputs");
             assert_eq!(other.iv_step, first.iv_step, "iv_step must match across inputs");
             assert_eq!(other.iv_min, first.iv_min, "iv_min must match across inputs");
             assert_eq!(other.iv_max, first.iv_max, "iv_max must match across inputs");
@@ -323,10 +298,7 @@ pub async fn run_sha1_mt_compact(
     }
 
     let kp_count = 0x1000usize;
-    let h_count = (first.hour_range[1] - first.hour_range[0] + 1) as usize;
-    let m_count = (first.minute_range[1] - first.minute_range[0] + 1) as usize;
-    let s_count = (first.second_range[1] - first.second_range[0] + 1) as usize;
-    let time_count = h_count * m_count * s_count;
+    let time_count = times.len();
     let output_len_u64 = (kp_count as u64)
         * (time_count as u64)
         * (input.len() as u64);
@@ -344,15 +316,17 @@ pub async fn run_sha1_mt_compact(
     let input_buffer = pool
         .create_init(input, BufferKind::Input, "rng_core_sha1_mt_compact_input_buffer")
         .buffer;
+    let time_buffer = pool
+        .create_init(times, BufferKind::Input, "rng_core_sha1_mt_compact_time_buffer")
+        .buffer;
 
-    let layout = input_output_counter_params_layout(&ctx.device);
+    let layout = input_output_counter_params_times_layout(&ctx.device);
     let pipeline = PipelineFactory::new(&ctx.device)
         .create_compute(&shader, &layout, "main");
 
     let readback = Readback::new(ctx);
 
     let mut results = Vec::new();
-    let mut base: u64 = 0;
     let wg = GpuKernelConfig::SHA1_MT.workgroup_size as u64;
     let max_groups = ctx
         .device
@@ -360,9 +334,10 @@ pub async fn run_sha1_mt_compact(
         .max_compute_workgroups_per_dimension
         .min(65535) as u64;
     let max_dispatch = max_groups.saturating_mul(wg);
-    while base < output_len_u64 {
-        let remaining = output_len_u64 - base;
-        let chunk_len = remaining.min(max_dispatch) as u32;
+    let mut queue = DispatchQueue::new(output_len_u64, max_dispatch);
+    while let Some(range) = queue.pop() {
+        let base = range.start;
+        let chunk_len = (range.end - range.start) as u32;
 
         let output_bytes = (MAX_RESULTS * std::mem::size_of::<GpuCandidate>()) as u64;
         let output_buffer = pool
@@ -388,6 +363,7 @@ pub async fn run_sha1_mt_compact(
             .buffer(1, &output_buffer)
             .buffer(2, &counter_buffer)
             .buffer(3, &params_buffer)
+            .buffer(4, &time_buffer)
             .build(&ctx.device, Some("rng_core_sha1_mt_compact_bind_group"));
 
         let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -412,6 +388,9 @@ pub async fn run_sha1_mt_compact(
             .read_buffer::<u32>(&counter_buffer, 1, Some("rng_core_sha1_mt_compact_count"))
             .await?;
         let count = count_vec[0] as usize;
+        if queue.split_if_overflowed(range, count, MAX_RESULTS) {
+            continue;
+        }
         if count > 0 {
             let read_len = count.min(MAX_RESULTS);
             let mut chunk = readback
@@ -423,13 +402,7 @@ pub async fn run_sha1_mt_compact(
                 )
                 .await?;
             results.append(&mut chunk);
-            if results.len() >= MAX_RESULTS {
-                results.truncate(MAX_RESULTS);
-                break;
-            }
         }
-
-        base += chunk_len as u64;
     }
 
     Ok(results)
@@ -438,11 +411,10 @@ pub async fn run_sha1_mt_compact(
 pub async fn run_sha1_seedhigh_filter(
     ctx: &infra::gpu::context::GpuContext,
     input: &[GpuInput],
+    times: &[u32],
     seed_high_list: &[u32],
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
-    let keypress_list: Vec<u32> = KeyPresses::iter_valid()
-        .map(|k| k.raw() as u32)
-        .collect();
+    keypress_list: &[u32],
+) -> Result<Vec<GpuCandidate>, GpuError> {
     let keypress_count = keypress_list.len() as u32;
     if keypress_count == 0 {
         return Ok(Vec::new());
@@ -455,22 +427,10 @@ pub async fn run_sha1_seedhigh_filter(
         return Ok(Vec::new());
     }
 
-    let first = &input[0];
-    if first.hour_range[1] < first.hour_range[0]
-        || first.minute_range[1] < first.minute_range[0]
-        || first.second_range[1] < first.second_range[0] {
+    if times.is_empty() {
         return Ok(Vec::new());
     }
 
-    #[cfg(debug_assertions)]
-    {
-        for other in input.iter().skip(1) {
-            assert_eq!(other.hour_range, first.hour_range, "hour_range must match across inputs");
-            assert_eq!(other.minute_range, first.minute_range, "minute_range must match across inputs");
-            assert_eq!(other.second_range, first.second_range, "second_range must match across inputs");
-        }
-    }
-
     let mut list = seed_high_list.to_vec();
     list.sort_unstable();
     list.dedup();
@@ -478,14 +438,9 @@ pub async fn run_sha1_seedhigh_filter(
         return Ok(Vec::new());
     }
 
-    let kp_count = keypress_list.len();
-    let h_count = (first.hour_range[1] - first.hour_range[0] + 1) as usize;
-    let m_count = (first.minute_range[1] - first.minute_range[0] + 1) as usize;
-    let s_count = (first.second_range[1] - first.second_range[0] + 1) as usize;
-    let time_count = h_count * m_count * s_count;
-    let output_len_u64 = (kp_count as u64)
-        * (time_count as u64)
-        * (input.len() as u64);
+    // 1スレッドが (日付, 時刻) を1つ受け持ち、キー入力はカーネルの中で回す
+    let time_count = times.len();
+    let output_len_u64 = (time_count as u64) * (input.len() as u64);
     if output_len_u64 == 0 {
         return Ok(Vec::new());
     }
@@ -500,14 +455,17 @@ pub async fn run_sha1_seedhigh_filter(
     let input_buffer = pool
         .create_init(input, BufferKind::Input, "rng_core_sha1_seedhigh_input_buffer")
         .buffer;
+    let time_buffer = pool
+        .create_init(times, BufferKind::Input, "rng_core_sha1_seedhigh_time_buffer")
+        .buffer;
     let list_buffer = pool
         .create_init(&list, BufferKind::Input, "rng_core_sha1_seedhigh_list_buffer")
         .buffer;
     let keypress_buffer = pool
-        .create_init(&keypress_list, BufferKind::Input, "rng_core_sha1_seedhigh_keypress_buffer")
+        .create_init(keypress_list, BufferKind::Input, "rng_core_sha1_seedhigh_keypress_buffer")
         .buffer;
 
-    let layout = input_list_output_counter_params_layout(&ctx.device);
+    let layout = input_list_output_counter_params_times_layout(&ctx.device);
     let pipeline = PipelineFactory::new(&ctx.device)
         .create_compute(&shader, &layout, "main");
 
@@ -535,9 +493,9 @@ pub async fn run_sha1_seedhigh_filter(
         .buffer(3, &output_buffer)
         .buffer(4, &counter_buffer)
         .buffer(5, &params_buffer)
+        .buffer(6, &time_buffer)
         .build(&ctx.device, Some("rng_core_sha1_seedhigh_bind_group"));
 
-    let mut base: u64 = 0;
     let wg = GpuKernelConfig::SHA1_MT.workgroup_size as u64;
     let max_groups = ctx
         .device
@@ -545,11 +503,9 @@ pub async fn run_sha1_seedhigh_filter(
         .max_compute_workgroups_per_dimension
         .min(65535) as u64;
     let max_dispatch = max_groups.saturating_mul(wg);
-    while base < output_len_u64 {
-        let remaining = output_len_u64 - base;
-        let chunk_len = remaining.min(max_dispatch) as u32;
+    let dispatch = |range: std::ops::Range<u64>| {
         let params = ListDispatchParams {
-            base_index: base,
+            base_index: range.start,
             total_len: output_len_u64,
             list_len: list.len() as u32,
             keypress_len: keypress_count,
@@ -568,13 +524,25 @@ pub async fn run_sha1_seedhigh_filter(
                 &mut pass,
                 &pipeline,
                 &bind_group,
-                chunk_len,
+                (range.end - range.start) as u32,
                 GpuKernelConfig::SHA1_MT.workgroup_size,
             );
         }
         ctx.queue.submit(Some(encoder.finish()));
+    };
+    let read_results = |count: usize| {
+        readback.read_buffer_with_pool::<GpuCandidate>(
+            &pool,
+            &output_buffer,
+            count.min(MAX_RESULTS),
+            Some("rng_core_sha1_seedhigh_readback"),
+        )
+    };
 
-        base += chunk_len as u64;
+    // ふつうは候補が少ないので、全範囲を続けて流してから一度だけ読む
+    let mut queue = DispatchQueue::new(output_len_u64, max_dispatch);
+    while let Some(range) = queue.pop() {
+        dispatch(range);
     }
 
     let count_vec = readback
@@ -584,16 +552,27 @@ pub async fn run_sha1_seedhigh_filter(
     if count == 0 {
         return Ok(Vec::new());
     }
+    if count <= MAX_RESULTS {
+        return Ok(read_results(count).await?);
+    }
 
-    let read_len = count.min(MAX_RESULTS);
-    let results = readback
-        .read_buffer_with_pool::<GpuCandidate>(
-            &pool,
-            &output_buffer,
-            read_len,
-            Some("rng_core_sha1_seedhigh_readback"),
-        )
-        .await?;
+    // あふれたときは範囲ごとにカウンタを戻して読み、さらにあふれた範囲は割ってやり直す
+    let mut results = Vec::new();
+    let mut queue = DispatchQueue::new(output_len_u64, max_dispatch);
+    while let Some(range) = queue.pop() {
+        ctx.queue.write_buffer(&counter_buffer, 0, bytemuck::bytes_of(&0u32));
+        dispatch(range.clone());
+        let count_vec = readback
+            .read_buffer::<u32>(&counter_buffer, 1, Some("rng_core_sha1_seedhigh_count"))
+            .await?;
+        let count = count_vec[0] as usize;
+        if queue.split_if_overflowed(range, count, MAX_RESULTS) {
+            continue;
+        }
+        if count > 0 {
+            results.append(&mut read_results(count).await?);
+        }
+    }
 
     Ok(results)
 }
@@ -602,14 +581,12 @@ pub async fn run_sha1_seedhigh_search(
     ctx: &infra::gpu::context::GpuContext,
     ds_config: DSConfig,
     datespec: GameDateSpec,
-    hour_range: [u32; 2],
-    minute_range: [u32; 2],
-    second_range: [u32; 2],
+    times: &TimeSet,
     iv_step: u32,
     iv_min: [u32; 6],
     iv_max: [u32; 6],
     batch_days: usize,
-) -> Result<Vec<GpuCandidate>, wgpu::BufferAsyncError> {
+) -> Result<Vec<GpuCandidate>, GpuError> {
     let cfg = GpuIvConfig {
         iv_step,
         _pad0: 0,
@@ -627,14 +604,13 @@ pub async fn run_sha1_seedhigh_search(
     let mut it = GPUInputIterator::new(
         ds_config,
         datespec,
-        hour_range,
-        minute_range,
-        second_range,
         iv_step,
         iv_min,
         iv_max,
     );
 
+    let times = times.packed();
+    let keypress_list: Vec<u32> = KeyPresses::iter_valid().map(|k| k.raw() as u32).collect();
     let mut results = Vec::new();
     let batch = batch_days.max(1);
     let sha_start = std::time::Instant::now();
@@ -644,12 +620,8 @@ pub async fn run_sha1_seedhigh_search(
             break;
         }
 
-        let mut chunk = run_sha1_seedhigh_filter(ctx, &inputs, &seed_highs).await?;
+        let mut chunk = run_sha1_seedhigh_filter(ctx, &inputs, &times, &seed_highs, &keypress_list).await?;
         results.append(&mut chunk);
-        if results.len() >= MAX_RESULTS {
-            results.truncate(MAX_RESULTS);
-            break;
-        }
     }
 
     //println!("sha1 filter elapsed: {:?}", sha_start.elapsed());
@@ -659,7 +631,7 @@ pub async fn run_sha1_seedhigh_search(
 #[cfg(all(test, not(ci)))]
 mod tests {
     use super::*;
-    use crate::models::{DSConfig, GameVersion};
+    use crate::models::{DSConfig, GameVersion, Hardware};
     use crate::gpu::input_layout::GpuIvConfig;
     use crate::gpu::mt_kernel;
     use crate::models::field_range::FieldRange;
@@ -670,10 +642,10 @@ mod tests {
     #[ignore]
     fn test_sha1_mt_matches_cpu_single_case() {
         pollster::block_on(async {
-            let ctx = GpuContext::new().await;
+            let ctx = GpuContext::new().await.expect("GPU context");
             let start = std::time::Instant::now();
 
-            let ds_config = DSConfig::new(GameVersion::White2, 0x10F7, false, 0x0009bf6d93ce);
+            let ds_config = DSConfig::new(GameVersion::White2, 0x10F7, Hardware::DsPhat, 0x0009bf6d93ce);
             
             let p = 2u32;
             let iv_min: [u32; 6] = [31u32, 31u32, 31u32, 8, 31u32, 31u32];
@@ -689,12 +661,9 @@ mod tests {
             ],
                 vcount_timer0_as_data5: ((ds_config.get_version_config().vcount.0 as u32) << 16) | (ds_config.Timer0 as u32),
                 mac: ds_config.MAC,
-                gxframe_xor_frame: 0x600_0008,
+                gxframe_xor_frame: ds_config.Hardware.gxframe_xor_frame(),
                 date_as_data8: 0x33082706,
-                hour_range:[0,23],
-                minute_range: [0,59],
-                second_range: [0,59],
-                _pad0: 0,
+                next_date_as_data8: 0x33082801,
                 iv_step: 2,
                 iv_min,
                 iv_max,
@@ -714,7 +683,9 @@ mod tests {
             let seed_elapsed = seed_start.elapsed();
 
             let sha_start = std::time::Instant::now();
-            let results = run_sha1_seedhigh_filter(&ctx, &inputs, &seed_highs)
+            let keypress_list: Vec<u32> = KeyPresses::iter_valid().map(|k| k.raw() as u32).collect();
+            let times = TimeSet::all().packed();
+            let results = run_sha1_seedhigh_filter(&ctx, &inputs, &times, &seed_highs, &keypress_list)
                 .await
                 .expect("run_sha1_seedhigh_filter failed");
             let sha_elapsed = sha_start.elapsed();
@@ -731,8 +702,8 @@ mod tests {
     #[ignore]
     fn test_sha1_seedhigh_search_smoke() {
         pollster::block_on(async {
-            let ctx = GpuContext::new().await;
-            let ds_config = DSConfig::new(GameVersion::White2, 0x10f7, false, 0x0009bf6d93ce);
+            let ctx = GpuContext::new().await.expect("GPU context");
+            let ds_config = DSConfig::new(GameVersion::White2, 0x10f7, Hardware::DsPhat, 0x0009bf6d93ce);
             let datespec = GameDateSpec {
                 year: FieldRange { min: 0, max:  99 },
                 month: FieldRange { min: 1, max: 12 },
@@ -743,9 +714,7 @@ mod tests {
                 &ctx,
                 ds_config,
                 datespec,
-                [0, 23],
-                [0, 59],
-                [0, 59],
+                &TimeSet::all(),
                 2,
                 [31u32, 31u32, 31u32, 8, 31u32, 31u32],
                 [31u32, 31u32, 31u32, 8, 31u32, 31u32],
@@ -32,10 +32,7 @@ struct GpuInput {
     mac: u64,
     gxframe_xor_frame: u32,
     date_as_data8: u32,
-    hour_range: array<u32, 2>,
-    minute_range: array<u32, 2>,
-    second_range: array<u32, 2>,
-    _pad0: u32,
+    next_date_as_data8: u32,
     iv_step: u32,
     iv_min: array<u32, 6>,
     iv_max: array<u32, 6>,
@@ -1,8 +1,8 @@
-// SHA-1 with GPU-side expansion of keypresses and time ranges.
+// SHA-1 with GPU-side expansion of keypresses and the time list.
 //
 // Uses:
 // - key presses are always enumerated as 0x2000..=0x2FFF (invalids have seed0=0).
-// - hour/minute/second ranges are expanded on GPU.
+// - times come from the time list (time9, low byte = day offset).
 
 struct GpuInput {
     nazo: array<u32, 5>,
@@ -10,10 +10,7 @@ struct GpuInput {
     mac: u64,
     gxframe_xor_frame: u32,
     date_as_data8: u32,
-    hour_range: array<u32, 2>,
-    minute_range: array<u32, 2>,
-    second_range: array<u32, 2>,
-    _pad0: u32,
+    next_date_as_data8: u32,
     iv_step: u32,
     iv_min: array<u32, 6>,
     iv_max: array<u32, 6>,
@@ -31,6 +28,10 @@ struct InputBuffer {
     data: array<GpuInput>,
 }
 
+struct TimeBuffer {
+    data: array<u32>,
+}
+
 struct OutputBuffer {
     data: array<GpuCandidate>,
 }
@@ -49,6 +50,9 @@ var<storage, read_write> output_buf: OutputBuffer;
 @group(0) @binding(2)
 var<storage, read> params: DispatchParams;
 
+@group(0) @binding(3)
+var<storage, read> time_buf: TimeBuffer;
+
 const KEY_A_BIT: u32 = 0u;
 const KEY_B_BIT: u32 = 1u;
 const KEY_SELECT_BIT: u32 = 2u;
@@ -110,14 +114,6 @@ fn write_be(bytes: ptr<function, array<u32, 64>>, index: u32, value: u32) {
     (*bytes)[index + 3u] = (value >> 0u) & 0xFFu;
 }
 
-fn time9_from_hms(hour: u32, minute: u32, second: u32) -> u32 {
-    let adjusted_hour = select(hour, hour + 40u, hour >= 12u);
-    let hex_hour = ((adjusted_hour / 10u) << 4u) | (adjusted_hour % 10u);
-    let hex_min = ((minute / 10u) << 4u) | (minute % 10u);
-    let hex_sec = ((second / 10u) << 4u) | (second % 10u);
-    return (hex_hour << 24u) | (hex_min << 16u) | (hex_sec << 8u);
-}
-
 @compute @workgroup_size(256)
 fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let global = params.base_index + u64(gid.x);
@@ -125,22 +121,9 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let input_len = arrayLength(&input_buf.data);
     if (input_len == 0u) { return; }
 
-    let cfg0 = input_buf.data[0];
-    let kp_count = KP_COUNT;
-
-    let h_min = cfg0.hour_range[0];
-    let h_max = cfg0.hour_range[1];
-    let m_min = cfg0.minute_range[0];
-    let m_max = cfg0.minute_range[1];
-    let s_min = cfg0.second_range[0];
-    let s_max = cfg0.second_range[1];
-    if (h_max < h_min || m_max < m_min || s_max < s_min) { return; }
-
-    let h_count = h_max - h_min + 1u;
-    let m_count = m_max - m_min + 1u;
-    let s_count = s_max - s_min + 1u;
-    let time_count = h_count * m_count * s_count;
-    let per_input = u64(kp_count) * u64(time_count);
+    let time_count = arrayLength(&time_buf.data);
+    if (time_count == 0u) { return; }
+    let per_input = u64(KP_COUNT) * u64(time_count);
 
     let input_idx = global / per_input;
     if (input_idx >= u64(input_len)) { return; }
@@ -149,20 +132,20 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let kp_idx = local / u64(time_count);
     let time_idx = local - kp_idx * u64(time_count);
 
-    let h_idx = time_idx / u64(m_count * s_count);
-    let rem = time_idx - h_idx * u64(m_count * s_count);
-    let m_idx = rem / u64(s_count);
-    let s_idx = rem - m_idx * u64(s_count);
-
-    let hour = h_min + u32(h_idx);
-    let minute = m_min + u32(m_idx);
-    let second = s_min + u32(s_idx);
-    let time9 = time9_from_hms(hour, minute, second);
+    let input = input_buf.data[u32(input_idx)];
+    // time list entries are time9 with the day offset in the low byte;
+    // entries past midnight use the next date, which is 0 when there is none
+    let packed = time_buf.data[u32(time_idx)];
+    let time9 = packed & 0xFFFFFF00u;
+    let next_day = (packed & 0xFFu) != 0u;
+    if (next_day && input.next_date_as_data8 == 0u) {
+        output_buf.data[gid.x] = GpuCandidate();
+        return;
+    }
+    let date8 = select(input.date_as_data8, input.next_date_as_data8, next_day);
 
     let key_presses = KEY_RANGE_START + u32(kp_idx);
 
-    let input = input_buf.data[u32(input_idx)];
-
     // Build message bytes (52 bytes)
     var bytes: array<u32, 64>;
     for (var j: u32 = 0u; j < 64u; j = j + 1u) {
@@ -185,7 +168,7 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let data7: u32 = gxframe_xor_frame_le ^ mac_middle_16;
     write_be(&bytes, off, data7); off = off + 4u;
 
-    write_be(&bytes, off, input.date_as_data8); off = off + 4u;
+    write_be(&bytes, off, date8); off = off + 4u;
     write_be(&bytes, off, time9); off = off + 4u;
 
     write_le(&bytes, off, 0u); off = off + 4u; // data10
@@ -268,7 +251,7 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
 
     var out: GpuCandidate;
     out.seed0 = seed0;
-    out.game_date = input.date_as_data8;
+    out.game_date = date8;
     out.game_time = time9;
     out.timer0 = input.vcount_timer0_as_data5;
     out.key_presses = key_presses;
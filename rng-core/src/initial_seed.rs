@@ -1,5 +1,12 @@
-use crate::sha_1::generate_initial_seed0;
-use crate::models::{DSConfig, GameTime, KeyPresses};
+use rayon::prelude::*;
+
+use crate::lcg::lcg_next;
+use crate::models::game_date::GameDate;
+use crate::models::game_date_iterator::GameDateSpec;
+use crate::models::{DSConfig, FieldRange, GameTime, GameTimeIterator, KeyPressFilter, KeyPresses, TimeSet};
+use crate::mt;
+use crate::result_base::ResultBase;
+use crate::sha_1::{Seed0Hasher, LANES};
 
 pub struct SeedResultBase {
     pub ds_config: DSConfig,
@@ -8,13 +15,93 @@ pub struct SeedResultBase {
     pub key_presses: KeyPresses,
 }
 
+/// `SeedIter` が1回に inner から取り出す件数
+const BATCH: usize = LANES * 32;
 
+/**
+(時刻, キー入力) の列に seed0 を付けて返す
 
+inner から `BATCH` 件ずつ取り出して SHA-1 を計算する。同じ日付・時刻が `LANES` 件以上続くところは
+`Seed0Hasher::prefix` の途中状態を使い回してキー入力だけを動かし、残りは `LANES` 件ずつまとめて計算する
+*/
 pub struct SeedIter<'a, I>
 where I:Iterator<Item = (GameTime, KeyPresses)>,
 {
     config: &'a DSConfig,
+    hasher: Seed0Hasher,
+    key_filter: KeyPressFilter,
     inner: I,
+    batch: Vec<(GameTime, KeyPresses)>,
+    seeds: Vec<u64>,
+    index: usize,
+    /// 同じ日付・時刻が続く区間のキー入力
+    keys: Vec<KeyPresses>,
+    /// 途中状態を使い回さずに計算する位置
+    scattered: Vec<usize>,
+}
+
+impl<'a, I> SeedIter<'a, I>
+where I:Iterator<Item = (GameTime, KeyPresses)>,
+{
+    pub fn new(config: &'a DSConfig, inner: I) -> Self {
+        Self {
+            config,
+            hasher: Seed0Hasher::new(config),
+            key_filter: KeyPressFilter::all(),
+            inner,
+            batch: Vec::with_capacity(BATCH),
+            seeds: Vec::with_capacity(BATCH),
+            index: 0,
+            keys: Vec::new(),
+            scattered: Vec::new(),
+        }
+    }
+
+    /// `filter` に合わないキー入力は SHA-1 を計算せずに飛ばす
+    pub fn with_key_filter(mut self, filter: KeyPressFilter) -> Self {
+        self.key_filter = filter;
+        self
+    }
+
+    /// 次の `BATCH` 件を取り出して seed0 を計算しておく。inner が尽きていれば false
+    fn fill(&mut self) -> bool {
+        let filter = &self.key_filter;
+        self.batch.clear();
+        self.batch.extend(self.inner.by_ref().filter(|&(_, k)| filter.matches(k)).take(BATCH));
+
+        self.seeds.clear();
+        self.scattered.clear();
+        for run in self.batch.chunk_by(|a, b| a.0 == b.0) {
+            if run.len() >= LANES {
+                let game_time = &run[0].0;
+                let prefix = self.hasher.prefix(game_time.get_date8_format(), game_time.get_time9_format());
+                self.keys.clear();
+                self.keys.extend(run.iter().map(|&(_, k)| k));
+                prefix.seed0_keys(&self.keys, &mut self.seeds);
+            } else {
+                let start = self.seeds.len();
+                self.scattered.extend(start..start + run.len());
+                self.seeds.resize(start + run.len(), 0);
+            }
+        }
+
+        let mut chunks = self.scattered.chunks_exact(LANES);
+        for idx in &mut chunks {
+            let date8 = std::array::from_fn(|l| self.batch[idx[l]].0.get_date8_format());
+            let time9 = std::array::from_fn(|l| self.batch[idx[l]].0.get_time9_format());
+            let keys = std::array::from_fn(|l| self.batch[idx[l]].1.raw() as u32);
+            let seeds = self.hasher.seed0_lanes::<LANES>(date8, time9, keys);
+            for (&i, seed0) in idx.iter().zip(seeds) {
+                self.seeds[i] = seed0;
+            }
+        }
+        for &i in chunks.remainder() {
+            let (game_time, key_presses) = &self.batch[i];
+            self.seeds[i] = self.hasher.seed0_at(game_time, *key_presses);
+        }
+        self.index = 0;
+        !self.batch.is_empty()
+    }
 }
 
 impl<'a, T> Iterator for SeedIter<'a, T>
@@ -23,18 +110,74 @@ where T: Iterator<Item = (GameTime, KeyPresses)>,
     type Item = SeedResultBase;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (game_time, key_presses) = self.inner.next()?;
-
-        let seed0 = generate_initial_seed0(self.config, &game_time, key_presses);
+        if self.index == self.batch.len() && !self.fill() {
+            return None;
+        }
+        let (game_time, key_presses) = self.batch[self.index];
+        let seed0 = self.seeds[self.index];
+        self.index += 1;
 
         Some(SeedResultBase { ds_config:*self.config, seed0, game_time, key_presses })
     }
 }
 
+/// `result_base_by_dates_cpu` で1つのタスクにまとめる時刻の数
+const TIMES_PER_TASK: usize = 60;
+
+/**
+GPU を使わずに `dates` の `times` × `key_filter` の seed を調べ、`keep` を通ったものを返す
+
+IV は `iv_step` で計算する。日付と `TIMES_PER_TASK` 個ずつの時刻ごとに並列に回し、結果は入力の順に並ぶ
+0 時をまたぐ時刻は翌日の日付になる (`GameTimeIterator` と同じ)
+*/
+pub fn result_base_by_dates_cpu(
+    config: DSConfig,
+    dates: &[GameDate],
+    times: &TimeSet,
+    key_filter: &KeyPressFilter,
+    iv_step: u8,
+    keep: impl Fn(&ResultBase) -> bool + Sync,
+) -> Vec<ResultBase> {
+    let keys: Vec<KeyPresses> = key_filter.iter().collect();
+    let keep = &keep;
+    let keys = &keys;
+    dates
+        .par_iter()
+        .flat_map(|&date| {
+            let spec = GameDateSpec {
+                year: FieldRange { min: date.year(), max: date.year() },
+                month: FieldRange { min: date.month(), max: date.month() },
+                day: FieldRange { min: date.day(), max: date.day() },
+            };
+            let game_times: Vec<GameTime> = GameTimeIterator::with_times(spec, times).collect();
+            game_times
+                .par_chunks(TIMES_PER_TASK)
+                .flat_map_iter(|chunk| {
+                    let inner = chunk.iter().flat_map(|&t| keys.iter().map(move |&k| (t, k)));
+                    SeedIter::new(&config, inner)
+                        .map(|r| {
+                            let seed1 = lcg_next(r.seed0);
+                            ResultBase {
+                                ds_config: config,
+                                seed0: r.seed0,
+                                seed1,
+                                game_time: r.game_time,
+                                key_presses: r.key_presses,
+                                ivs: mt::mt_1(seed1, iv_step),
+                            }
+                        })
+                        .filter(|base| keep(base))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
 
 #[cfg(test)]
 mod tests {
-    use crate::models::GameVersion;
+    use crate::models::{GameVersion, Hardware};
 
     use super::*;
 
@@ -45,7 +188,7 @@ mod tests {
             Version: GameVersion::Black,
             Timer0: 0xc7a,
             MAC: 0x0009bf6d93ce,
-            IsDSLite: false,
+            Hardware: Hardware::DsPhat,
             // 他のフィールドがあれば適当に固定
         };
 
@@ -65,10 +208,7 @@ mod tests {
             (t2, k2),
         ].into_iter();
 
-        let mut iter: SeedIter<'_, std::vec::IntoIter<(GameTime, KeyPresses)>> = SeedIter{
-            config: &config,
-            inner,
-        };
+        let mut iter: SeedIter<'_, std::vec::IntoIter<(GameTime, KeyPresses)>> = SeedIter::new(&config, inner);
 
         // --- 1個目 ---
         let r1 = iter.next().expect("first item");
@@ -88,4 +228,82 @@ mod tests {
         assert_ne!(r1.seed0, 0);
         assert_ne!(r2.seed0, 0);
     }
+
+    #[test]
+    fn seed_iterator_skips_filtered_keys() {
+        let config = DSConfig::new(GameVersion::Black, 0xc7a, Hardware::DsPhat, 0x0009bf6d93ce);
+        let t = GameTime::new(26, 1, 24, 12, 0, 0);
+        let inner = KeyPresses::iter_valid().map(move |k| (t, k));
+        let filter = KeyPressFilter::all().max_keys(1);
+
+        let keys: Vec<KeyPresses> = SeedIter::new(&config, inner)
+            .with_key_filter(filter.clone())
+            .map(|r| r.key_presses)
+            .collect();
+        assert_eq!(keys, filter.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn batched_lanes_match_single_hashes() {
+        let config = DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce);
+        let hasher = Seed0Hasher::new(&config);
+        // BATCH をまたぎ、LANES で割り切れない件数・時刻とキーが混ざった列
+        let keys: Vec<KeyPresses> = KeyPresses::iter_valid().take(7).collect();
+        let inner: Vec<(GameTime, KeyPresses)> = GameTimeIterator::with_times(
+            GameDateSpec {
+                year: FieldRange { min: 99, max: 99 },
+                month: FieldRange { min: 12, max: 12 },
+                day: FieldRange { min: 31, max: 31 },
+            },
+            &TimeSet::window((23, 59, 0), (23, 59, 59)).unwrap(),
+        )
+        .flat_map(|t| keys.iter().map(move |&k| (t, k)))
+        .collect();
+        assert_eq!(inner.len(), 60 * 7);
+
+        let results: Vec<SeedResultBase> = SeedIter::new(&config, inner.clone().into_iter()).collect();
+        assert_eq!(results.len(), inner.len());
+        for (r, (t, k)) in results.iter().zip(&inner) {
+            assert_eq!((r.game_time, r.key_presses), (*t, *k));
+            assert_eq!(r.seed0, hasher.seed0_at(t, *k));
+        }
+    }
+
+    #[test]
+    fn runs_of_one_time_match_single_hashes() {
+        let config = DSConfig::new(GameVersion::Black, 0xc7a, Hardware::DsLite, 0x0009bf6d93ce);
+        let hasher = Seed0Hasher::new(&config);
+        let keys: Vec<KeyPresses> = KeyPresses::iter_valid().collect();
+        let no_keys = KeyPresses::new(0x2fff);
+        let mut inner = Vec::new();
+        // 時刻が1件ずつ変わる区間、LANES 件以上続く区間、BATCH をまたいで続く区間を混ぜる
+        for (second, run) in (50..).zip([1, 1, LANES - 1, LANES * 3 + 1, 2, BATCH + 5, 1]) {
+            let t = GameTime::new(99, 12, 31, 23, 59, second);
+            inner.extend(keys.iter().take(run).map(|&k| (t, k)));
+            inner.push((t, no_keys));
+        }
+
+        let results: Vec<SeedResultBase> = SeedIter::new(&config, inner.clone().into_iter()).collect();
+        assert_eq!(results.len(), inner.len());
+        for (r, (t, k)) in results.iter().zip(&inner) {
+            assert_eq!((r.game_time, r.key_presses), (*t, *k));
+            assert_eq!(r.seed0, hasher.seed0_at(t, *k));
+        }
+    }
+
+    #[test]
+    fn cpu_search_finds_a_known_seed() {
+        let config = DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce);
+        let no_keys = KeyPresses::new(0x2fff);
+        let filter = KeyPressFilter::all().max_keys(0);
+        let times = TimeSet::window((12, 32, 10), (12, 32, 15)).unwrap();
+
+        let results = result_base_by_dates_cpu(config, &[GameDate::new(33, 8, 27)], &times, &filter, 16, |base| {
+            base.game_time.second() == 13
+        });
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].seed0, 0xB38133D93163C727);
+        assert_eq!(results[0].key_presses, no_keys);
+        assert_eq!(results[0].ivs, mt::mt_1(lcg_next(0xB38133D93163C727), 16));
+    }
 }
@@ -0,0 +1,272 @@
+/*!
+MT の 32ビットシード → IV シグネチャ (30ビット) の事前計算テーブル
+
+GPU の `run_mt_seedhigh_candidates` は IV 条件ごとに 2^32 通りを総当たりするが、
+消費数ごとにこの表を一度作っておけば、任意の IV 範囲の seed_high 候補を
+CPU で表を走査するだけで求められる。表はファイルに書き出して mmap で読むので、
+実行をまたいでも、別のマシンにコピーしても使い回せる
+
+ファイル形式 (リトルエンディアン)
+- 0..8   マジック `SGIVSIG\0`
+- 8..12  バージョン (u32)
+- 12..16 消費数 step (u32)
+- 16..24 先頭のシード seed_start (u64)
+- 24..32 シードの個数 count (u64)
+- 32..   シグネチャ (u32) × count
+
+全シード分 (count = 2^32) だと 16 GiB になる
+*/
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::mt::{iv_sets, mt_32};
+
+const MAGIC: [u8; 8] = *b"SGIVSIG\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+const SEED_SPACE: u64 = 1 << 32;
+/// 生成時に一度にメモリに載せるシード数
+const GENERATE_CHUNK: u64 = 1 << 22;
+/// mt_32 のスタック版で計算できる最大の消費数
+const MT_32_MAX_STEP: u32 = 20;
+
+/// IV を H が上位になるように 5ビットずつ詰める
+pub fn pack_ivs(ivs: [u8; 6]) -> u32 {
+    ivs.iter().fold(0u32, |acc, &iv| (acc << 5) | (iv as u32 & 0x1F))
+}
+
+pub fn unpack_ivs(signature: u32) -> [u8; 6] {
+    std::array::from_fn(|i| ((signature >> (5 * (5 - i))) & 0x1F) as u8)
+}
+
+/// 32ビットシード・消費数 step の IV シグネチャ
+pub fn signature(seed: u32, step: u32) -> u32 {
+    let ivs = if step <= MT_32_MAX_STEP {
+        mt_32(seed, step as u8)
+    } else {
+        iv_sets(seed, step).next().unwrap_or_default()
+    };
+    pack_ivs(ivs)
+}
+
+/// `seeds` の範囲のシグネチャを rayon で並列に計算する
+pub fn generate(step: u32, seeds: Range<u64>) -> Vec<u32> {
+    let end = seeds.end.min(SEED_SPACE);
+    (seeds.start..end)
+        .into_par_iter()
+        .map(|seed| signature(seed as u32, step))
+        .collect()
+}
+
+/// 表を計算してファイルに書き出す。全体を一度にメモリに載せないよう少しずつ書く
+pub fn write_table(path: impl AsRef<Path>, step: u32, seeds: Range<u64>) -> io::Result<()> {
+    let end = seeds.end.min(SEED_SPACE);
+    let start = seeds.start.min(end);
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&step.to_le_bytes())?;
+    writer.write_all(&start.to_le_bytes())?;
+    writer.write_all(&(end - start).to_le_bytes())?;
+
+    let mut chunk_start = start;
+    while chunk_start < end {
+        let chunk_end = (chunk_start + GENERATE_CHUNK).min(end);
+        let chunk = generate(step, chunk_start..chunk_end);
+        for sig in chunk {
+            writer.write_all(&sig.to_le_bytes())?;
+        }
+        chunk_start = chunk_end;
+    }
+    writer.flush()
+}
+
+/// mmap したシグネチャ表
+pub struct IvSignatureTable {
+    mmap: Mmap,
+    step: u32,
+    seed_start: u64,
+    count: u64,
+}
+
+impl IvSignatureTable {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: 読み取り専用で開いている。表を書き換えるプロセスとは併用しない前提
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        if mmap.len() < HEADER_LEN || mmap[0..8] != MAGIC {
+            return Err(invalid_data("not an IV signature table"));
+        }
+        let version = u32::from_le_bytes(mmap[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported IV signature table version {version}")));
+        }
+        let step = u32::from_le_bytes(mmap[12..16].try_into().unwrap());
+        let seed_start = u64::from_le_bytes(mmap[16..24].try_into().unwrap());
+        let count = u64::from_le_bytes(mmap[24..32].try_into().unwrap());
+
+        // ヘッダーは信用できないので、足し算・掛け算のあふれも壊れたファイルとして扱う
+        let seed_end = seed_start.checked_add(count).filter(|&end| end <= SEED_SPACE);
+        let expected = count.checked_mul(4).and_then(|len| len.checked_add(HEADER_LEN as u64));
+        match (seed_end, expected) {
+            (Some(_), Some(expected)) if mmap.len() as u64 == expected => {}
+            _ => {
+                return Err(invalid_data(format!(
+                    "IV signature table is truncated or corrupt (header says {count} seeds from {seed_start}, found {} bytes)",
+                    mmap.len()
+                )));
+            }
+        }
+
+        Ok(Self { mmap, step, seed_start, count })
+    }
+
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// 表に含まれるシードの範囲
+    pub fn seeds(&self) -> Range<u64> {
+        self.seed_start..self.seed_start + self.count
+    }
+
+    /// シグネチャの生バイト列 (4 バイトずつリトルエンディアン)
+    fn signature_bytes(&self) -> &[u8] {
+        &self.mmap[HEADER_LEN..]
+    }
+
+    pub fn signature(&self, seed: u32) -> Option<u32> {
+        let index = (seed as u64).checked_sub(self.seed_start)?;
+        let offset = usize::try_from(index).ok()?.checked_mul(4)?;
+        let bytes = self.signature_bytes().get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn ivs(&self, seed: u32) -> Option<[u8; 6]> {
+        self.signature(seed).map(unpack_ivs)
+    }
+
+    /// IV がすべて [iv_min, iv_max] に入るシードを昇順で返す
+    pub fn candidates(&self, iv_min: [u8; 6], iv_max: [u8; 6]) -> Vec<u32> {
+        let seed_start = self.seed_start;
+        let mut out: Vec<u32> = self
+            .signature_bytes()
+            .par_chunks_exact(4)
+            .enumerate()
+            .filter(|(_, bytes)| {
+                let ivs = unpack_ivs(u32::from_le_bytes((*bytes).try_into().unwrap()));
+                (0..6).all(|i| iv_min[i] <= ivs[i] && ivs[i] <= iv_max[i])
+            })
+            .map(|(i, _)| (seed_start + i as u64) as u32)
+            .collect();
+        out.par_sort_unstable();
+        out
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rng_core_{}_{}.ivsig", name, std::process::id()))
+    }
+
+    #[test]
+    fn pack_roundtrip() {
+        let ivs = [31, 0, 17, 5, 30, 1];
+        assert_eq!(unpack_ivs(pack_ivs(ivs)), ivs);
+        assert_eq!(pack_ivs([31; 6]), (1 << 30) - 1);
+    }
+
+    #[test]
+    fn table_roundtrip_and_candidates() {
+        let path = temp_path("roundtrip");
+        let seeds = 0x9B3E_0000u64..0x9B3E_0000 + 4096;
+        write_table(&path, 17, seeds.clone()).unwrap();
+
+        let table = IvSignatureTable::open(&path).unwrap();
+        assert_eq!(table.step(), 17);
+        assert_eq!(table.seeds(), seeds);
+        assert_eq!(table.ivs(0x9B3E_0010), Some(mt_32(0x9B3E_0010, 17)));
+        assert_eq!(table.ivs(0x9B3D_FFFF), None);
+
+        let (iv_min, iv_max) = ([20, 0, 0, 0, 0, 20], [31; 6]);
+        let expected: Vec<u32> = seeds
+            .map(|s| s as u32)
+            .filter(|&s| {
+                let ivs = mt_32(s, 17);
+                ivs[0] >= 20 && ivs[5] >= 20
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(table.candidates(iv_min, iv_max), expected);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn step_beyond_mt_32_limit() {
+        let seed = 0xA90C_98ED;
+        assert_eq!(unpack_ivs(signature(seed, 40)), iv_sets(seed, 40).next().unwrap());
+    }
+
+    #[test]
+    fn open_rejects_truncated_file() {
+        let path = temp_path("truncated");
+        write_table(&path, 0, 0..16).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+
+        let err = IvSignatureTable::open(&path).err().expect("should fail");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn open_rejects_overflowing_header() {
+        let path = temp_path("overflow");
+        write_table(&path, 0, 0..16).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        // count * 4 があふれる / seed_start + count があふれる
+        for (seed_start, count) in [(0u64, u64::MAX / 2), (u64::MAX - 4, 16)] {
+            let mut corrupt = bytes.clone();
+            corrupt[16..24].copy_from_slice(&seed_start.to_le_bytes());
+            corrupt[24..32].copy_from_slice(&count.to_le_bytes());
+            std::fs::write(&path, &corrupt).unwrap();
+            let err = IvSignatureTable::open(&path).err().expect("should fail");
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn signatures_are_read_as_little_endian() {
+        let path = temp_path("endian");
+        write_table(&path, 3, 100..104).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&pack_ivs([1, 2, 3, 4, 5, 6]).to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+
+        let table = IvSignatureTable::open(&path).unwrap();
+        assert_eq!(table.ivs(100), Some([1, 2, 3, 4, 5, 6]));
+        assert_eq!(table.ivs(101), Some(mt_32(101, 3)));
+        assert_eq!(table.ivs(104), None);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,10 +1,11 @@
 use super::Lcg;
 use super::OffsetType;
+use crate::error::ConfigError;
 
 impl Lcg {
-    pub fn tid_sid(&mut self, offset_type: OffsetType) -> (u16, u16) {
+    pub fn tid_sid(&mut self, offset_type: OffsetType) -> Result<(u16, u16), ConfigError> {
         if self.step <= 1 {
-            panic!("LCG state has not been advanced yet. Call offset_seed0 or offset_seed1 first.");
+            return Err(ConfigError::LcgNotOffset { step: self.step });
         }
         let next: u32 = (self.next() >> 32) as u32;
         // TIDはnext>>32の下16ビット
@@ -24,7 +25,7 @@ impl Lcg {
             },
         }
 
-        (tid, sid)
+        Ok((tid, sid))
     }
 }
 
@@ -46,8 +47,17 @@ mod tests {
     fn test_tid_sid() {
         let mut seed = Lcg::new(0x48B96278DC6233AB);
         seed.offset_seed1(OffsetType::Bw1Start);
-        let (tid, _sid) = seed.tid_sid(OffsetType::Bw1Start);
+        let (tid, _sid) = seed.tid_sid(OffsetType::Bw1Start).unwrap();
         assert_eq!(tid, 5683);
         assert_eq!(_sid, 47868, "SID is {:X}", _sid);
     }
+
+    #[test]
+    fn tid_sid_requires_offset() {
+        let mut seed = Lcg::new(0x48B96278DC6233AB);
+        assert_eq!(
+            seed.tid_sid(OffsetType::Bw1Start),
+            Err(ConfigError::LcgNotOffset { step: 0 })
+        );
+    }
 }
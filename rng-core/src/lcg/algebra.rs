@@ -0,0 +1,156 @@
+/*!
+LCG の代数
+
+x_{n+1} = a * x_n + c (mod 2^64) は a ≡ 1 (mod 4)、c が奇数なので周期がちょうど 2^64 になる
+n 回進めたものも x_n = mul * x_0 + add の形なので、2 のべき乗回ぶんの (mul, add) を表にしておけば
+任意の n を O(log n) で進められる。戻すのは 2^64 - n 回進めるのと同じ
+*/
+
+use super::{LCG_INCREMENT, LCG_MULTIPLIER};
+
+/// `LCG_MULTIPLIER` の mod 2^64 での逆数
+pub const LCG_MULTIPLIER_INV: u64 = inverse(LCG_MULTIPLIER);
+/// 1 回戻すときの加算 (x_{n-1} = a^-1 * x_n + この値)
+pub const LCG_INCREMENT_INV: u64 = LCG_MULTIPLIER_INV.wrapping_mul(LCG_INCREMENT).wrapping_neg();
+
+/// `JUMP_TABLE[i]` は 2^i 回進める (mul, add)
+pub const JUMP_TABLE: [(u64, u64); 64] = jump_table();
+
+/// 奇数の mod 2^64 での逆数 (ニュートン法。1 回ごとに正しいビット数が倍になる)
+const fn inverse(a: u64) -> u64 {
+    let mut x = a;
+    let mut i = 0;
+    while i < 6 {
+        x = x.wrapping_mul(2u64.wrapping_sub(a.wrapping_mul(x)));
+        i += 1;
+    }
+    x
+}
+
+const fn jump_table() -> [(u64, u64); 64] {
+    let mut table = [(0u64, 0u64); 64];
+    let (mut mul, mut add) = (LCG_MULTIPLIER, LCG_INCREMENT);
+    let mut i = 0;
+    while i < 64 {
+        table[i] = (mul, add);
+        // f^{2^{i+1}}(x) = mul * (mul * x + add) + add
+        add = mul.wrapping_mul(add).wrapping_add(add);
+        mul = mul.wrapping_mul(mul);
+        i += 1;
+    }
+    table
+}
+
+/// n 回進める (mul, add)。x_n = mul * x_0 + add
+pub const fn advance_params(n: u64) -> (u64, u64) {
+    let (mut mul, mut add) = (1u64, 0u64);
+    let mut i = 0;
+    while i < 64 {
+        if n >> i & 1 == 1 {
+            let (m, a) = JUMP_TABLE[i];
+            mul = mul.wrapping_mul(m);
+            add = add.wrapping_mul(m).wrapping_add(a);
+        }
+        i += 1;
+    }
+    (mul, add)
+}
+
+pub const fn lcg_advance(seed: u64, n: u64) -> u64 {
+    let (mul, add) = advance_params(n);
+    seed.wrapping_mul(mul).wrapping_add(add)
+}
+
+pub const fn lcg_retreat(seed: u64, n: u64) -> u64 {
+    lcg_advance(seed, n.wrapping_neg())
+}
+
+/**
+`from` から何回進めると `to` になるか (0 以上 2^64 未満でただ一つ決まる)
+
+周期が 2^64 なので 2^i 回進めても下位 i ビットは変わらず、i ビット目だけが必ず反転する
+下のビットから順に、`to` と違っていれば 2^i 回進めて合わせていく
+*/
+pub const fn distance(from: u64, to: u64) -> u64 {
+    let mut x = from;
+    let mut n = 0u64;
+    let mut i = 0;
+    while i < 64 {
+        if (x ^ to) >> i & 1 == 1 {
+            let (m, a) = JUMP_TABLE[i];
+            x = x.wrapping_mul(m).wrapping_add(a);
+            n |= 1 << i;
+        }
+        i += 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcg::{lcg_next, lcg_prev, Lcg};
+
+    const SEEDS: [u64; 4] = [0, 0x9B3E7C4BC185AE31, 0xFFFF_FFFF_FFFF_FFFF, 0x1234_5678_9ABC_DEF0];
+
+    #[test]
+    fn inverse_undoes_next() {
+        assert_eq!(LCG_MULTIPLIER.wrapping_mul(LCG_MULTIPLIER_INV), 1);
+        for seed in SEEDS {
+            let mut x = seed;
+            for _ in 0..10_000 {
+                assert_eq!(lcg_prev(lcg_next(x)), x);
+                x = lcg_next(x);
+            }
+        }
+    }
+
+    #[test]
+    fn jumps_match_repeated_next() {
+        for seed in SEEDS {
+            let mut x = seed;
+            for n in 0..5_000u64 {
+                assert_eq!(lcg_advance(seed, n), x, "seed {seed:X} n {n}");
+                assert_eq!(lcg_retreat(x, n), seed, "seed {seed:X} n {n}");
+                assert_eq!(distance(seed, x), n, "seed {seed:X} n {n}");
+                x = lcg_next(x);
+            }
+        }
+    }
+
+    #[test]
+    fn table_entries_are_powers_of_two() {
+        let mut x = 0x9B3E7C4BC185AE31u64;
+        let start = x;
+        for n in 1..=(1u64 << 16) {
+            x = lcg_next(x);
+            if n.is_power_of_two() {
+                let (m, a) = JUMP_TABLE[n.trailing_zeros() as usize];
+                assert_eq!(start.wrapping_mul(m).wrapping_add(a), x, "2^{}", n.trailing_zeros());
+            }
+        }
+    }
+
+    #[test]
+    fn distance_over_large_jumps() {
+        for seed in SEEDS {
+            for n in [1u64 << 32, 0xDEAD_BEEF_CAFE, u64::MAX, 1 << 63, 12_345_678_901_234_567] {
+                let to = lcg_advance(seed, n);
+                assert_eq!(distance(seed, to), n);
+                assert_eq!(lcg_retreat(to, n), seed);
+            }
+            // 1 回戻すのは 2^64 - 1 回進めるのと同じ
+            assert_eq!(distance(seed, lcg_prev(seed)), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn lcg_prev_and_retreat_track_step() {
+        let mut lcg = Lcg::new(0x9B3E7C4BC185AE31);
+        lcg.advance(40);
+        assert_eq!(lcg.prev(), lcg_advance(0x9B3E7C4BC185AE31, 39));
+        assert_eq!(lcg.step, 39);
+        assert_eq!(lcg.retreat(39), 0x9B3E7C4BC185AE31);
+        assert_eq!(lcg.step, 0);
+    }
+}
@@ -0,0 +1,265 @@
+/*!
+観測した結果の並びから現在の消費数を割り出す
+
+起動後に捕まえたポケモンの性格、草むらで出た・出なかった、隠し穴が埋まった場所などを順に記録し、
+前の観測から何消費くらい空いたか (ぶれの幅つき) と一緒に渡す
+候補の seed0 それぞれについてオフセット後から観測を当てはめていき、
+ぶれの合計が小さいものから順に返す
+*/
+
+use std::collections::HashMap;
+
+use crate::error::ConfigError;
+
+use super::grotto::Grottos;
+use super::nature::Nature;
+use super::wild_poke::WildPoke;
+use super::{Lcg, OffsetType};
+
+/// 1 回の観測で分かったこと
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Observation {
+    /// すぐ捕まえたポケモンの性格 ([`Lcg::get_nature`])
+    Nature(Nature),
+    /// 野生が出た。性格が分かっていれば入れる
+    Wild(Option<Nature>),
+    /// 野生が出なかった
+    NoWild,
+    /// 隠し穴を空の状態から埋めたときに埋まった場所 (0-19)
+    GrottoFills(Vec<usize>),
+}
+
+impl Observation {
+    fn matches(&self, lcg: &Lcg, offset_type: OffsetType) -> bool {
+        let mut lcg = *lcg;
+        match self {
+            Observation::Nature(nature) => lcg.get_nature() == *nature,
+            Observation::Wild(nature) => {
+                let poke = wild_poke(&mut lcg, offset_type);
+                poke.slot.is_some() && nature.as_ref().is_none_or(|n| poke.nature.as_ref() == Some(n))
+            }
+            Observation::NoWild => wild_poke(&mut lcg, offset_type).slot.is_none(),
+            Observation::GrottoFills(filled) => {
+                let mut grottos = Grottos::new();
+                grottos.fill_grottos(&lcg);
+                (0..20).all(|i| grottos.grottos[i].sub_slot().is_some() == filled.contains(&i))
+            }
+        }
+    }
+}
+
+fn wild_poke(lcg: &mut Lcg, offset_type: OffsetType) -> WildPoke {
+    if offset_type.is_bw1() { lcg.get_wild_poke_bw1() } else { lcg.get_wild_poke_bw2() }
+}
+
+/**
+`ObservedStep::tolerance` の上限
+
+観測ごとに前後 `tolerance` 消費を1つずつ調べるので、これより大きい幅はエラーにする
+*/
+pub const MAX_TOLERANCE: u64 = 10_000;
+
+/// 観測と、前の観測 (最初はオフセットの終わり) からの消費数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedStep {
+    pub observation: Observation,
+    /// だいたい何消費空いたか
+    pub gap: u64,
+    /// `gap` からどれだけずれてもよいか (前後それぞれ、[`MAX_TOLERANCE`] まで)
+    pub tolerance: u64,
+}
+
+impl ObservedStep {
+    pub fn new(observation: Observation, gap: u64, tolerance: u64) -> Self {
+        Self { observation, gap, tolerance }
+    }
+}
+
+/// 観測と矛盾しない消費数の候補
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvanceCandidate {
+    pub seed0: u64,
+    /// 最後の観測をした時点の消費数 (seed0 からの `Lcg::step`)
+    pub advance: u64,
+    /// 各観測をした時点の消費数
+    pub positions: Vec<u64>,
+    /// `gap` からのずれの合計。小さいほどそれらしい
+    pub deviation: u64,
+}
+
+/**
+`seeds` それぞれについて `offset_type` のオフセット後から `steps` を当てはめ、
+すべての観測と合う消費数をずれの合計が小さい順に返す
+
+同じ seed0 で同じ消費数に着く道筋が複数あるときはずれの小さい方だけを残す
+`tolerance` が [`MAX_TOLERANCE`] を超える観測があれば、何も調べずにエラーを返す
+*/
+pub fn identify_advance(
+    seeds: &[u64],
+    offset_type: OffsetType,
+    steps: &[ObservedStep],
+) -> Result<Vec<AdvanceCandidate>, ConfigError> {
+    if let Some(step) = steps.iter().find(|s| s.tolerance > MAX_TOLERANCE) {
+        return Err(ConfigError::ToleranceTooLarge { tolerance: step.tolerance, max: MAX_TOLERANCE });
+    }
+    let mut found = Vec::new();
+    for &seed0 in seeds {
+        let mut lcg = Lcg::new(seed0);
+        lcg.offset_seed0(offset_type);
+
+        // 消費数 -> (ずれの合計, それまでの観測位置)
+        let mut frontier: HashMap<u64, (u64, Vec<u64>)> = HashMap::from([(lcg.step, (0, Vec::new()))]);
+        for step in steps {
+            let mut next: HashMap<u64, (u64, Vec<u64>)> = HashMap::new();
+            for (&pos, (deviation, positions)) in &frontier {
+                // gap・tolerance は呼び出し側の値なので、足してあふれたら u64::MAX で止める
+                let expected = pos.saturating_add(step.gap);
+                let lo = expected.saturating_sub(step.tolerance).max(pos);
+                let hi = expected.saturating_add(step.tolerance);
+                let mut at = Lcg { state: super::lcg_advance(seed0, lo), step: lo };
+                for target in lo..=hi {
+                    if target > lo {
+                        at.next();
+                    }
+                    if step.observation.matches(&at, offset_type) {
+                        let deviation = deviation.saturating_add(target.abs_diff(expected));
+                        if next.get(&target).is_none_or(|(d, _)| deviation < *d) {
+                            let mut positions = positions.clone();
+                            positions.push(target);
+                            next.insert(target, (deviation, positions));
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        found.extend(frontier.into_iter().map(|(advance, (deviation, positions))| AdvanceCandidate {
+            seed0,
+            advance,
+            positions,
+            deviation,
+        }));
+    }
+    found.sort_by_key(|c| (c.deviation, c.advance, c.seed0));
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED0: u64 = 0xf9d9dd91248eecb0;
+
+    fn nature_at(seed0: u64, advance: u64) -> Nature {
+        let mut lcg = Lcg::new(seed0);
+        lcg.advance(advance);
+        lcg.get_nature()
+    }
+
+    fn offset(seed0: u64, offset_type: OffsetType) -> u64 {
+        Lcg::new(seed0).offset_seed0(offset_type)
+    }
+
+    #[test]
+    fn exact_gaps_find_the_true_advance() {
+        let start = offset(SEED0, OffsetType::BW2Continue);
+        let positions = [start + 30, start + 75, start + 140, start + 150];
+        let mut prev = start;
+        let steps: Vec<ObservedStep> = positions
+            .iter()
+            .map(|&p| {
+                let step = ObservedStep::new(Observation::Nature(nature_at(SEED0, p)), p - prev, 0);
+                prev = p;
+                step
+            })
+            .collect();
+
+        let found = identify_advance(&[SEED0], OffsetType::BW2Continue, &steps).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].advance, start + 150);
+        assert_eq!(found[0].positions, positions);
+        assert_eq!(found[0].deviation, 0);
+    }
+
+    #[test]
+    fn fuzzy_gaps_rank_the_true_path_first() {
+        let start = offset(SEED0, OffsetType::BW2Continue);
+        let positions = [start + 12, start + 48, start + 97, start + 131, start + 190, start + 233];
+        let mut prev = start;
+        let steps: Vec<ObservedStep> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                // 申告する間隔を ±2 ずらす
+                let gap = (p - prev) as i64 + [2, -1, 0, 1, -2, 1][i];
+                prev = p;
+                ObservedStep::new(Observation::Nature(nature_at(SEED0, p)), gap as u64, 3)
+            })
+            .collect();
+
+        let found = identify_advance(&[SEED0, 0x113E10468C85C156], OffsetType::BW2Continue, &steps).unwrap();
+        assert_eq!(found[0].seed0, SEED0);
+        assert_eq!(found[0].advance, start + 233);
+        assert!(found.iter().all(|c| c.positions.len() == positions.len()));
+    }
+
+    #[test]
+    fn wild_and_grotto_observations() {
+        let start = offset(SEED0, OffsetType::BW2Continue);
+        let mut lcg = Lcg::new(SEED0);
+        lcg.advance(start);
+        let mut steps = Vec::new();
+        let mut prev = start;
+        for _ in 0..4 {
+            lcg.advance(7);
+            let poke = lcg.get_wild_poke_bw2();
+            let observation = match poke.slot {
+                Some(_) => Observation::Wild(poke.nature),
+                None => Observation::NoWild,
+            };
+            steps.push(ObservedStep::new(observation, lcg.step - prev, 1));
+            prev = lcg.step;
+        }
+        lcg.advance(20);
+        let mut grottos = Grottos::new();
+        grottos.fill_grottos(&lcg);
+        let filled: Vec<usize> = (0..20).filter(|&i| grottos.grottos[i].sub_slot().is_some()).collect();
+        steps.push(ObservedStep::new(Observation::GrottoFills(filled), lcg.step - prev, 1));
+
+        let found = identify_advance(&[SEED0], OffsetType::BW2Continue, &steps).unwrap();
+        assert_eq!(found[0].advance, lcg.step);
+        assert_eq!(found[0].deviation, 0);
+    }
+
+    #[test]
+    fn no_match_returns_empty() {
+        let start = offset(SEED0, OffsetType::Bw1Continue);
+        let wrong = Nature::new((nature_at(SEED0, start + 5).id() + 1) % Nature::MAX);
+        let steps = [ObservedStep::new(Observation::Nature(wrong), 5, 0)];
+        assert!(identify_advance(&[SEED0], OffsetType::Bw1Continue, &steps).unwrap().is_empty());
+    }
+
+    #[test]
+    fn huge_gaps_saturate_instead_of_overflowing() {
+        let last = nature_at(SEED0, u64::MAX);
+        let steps = [ObservedStep::new(Observation::Nature(last), u64::MAX, 2)];
+        let found = identify_advance(&[SEED0], OffsetType::BW2Continue, &steps).unwrap();
+        assert!(found.iter().all(|c| c.advance >= u64::MAX - 2));
+        assert!(found.iter().any(|c| c.advance == u64::MAX && c.deviation == 0));
+    }
+
+    #[test]
+    fn huge_tolerance_is_rejected_up_front() {
+        let nature = nature_at(SEED0, 100);
+        let steps = [
+            ObservedStep::new(Observation::Nature(nature.clone()), 50, MAX_TOLERANCE),
+            ObservedStep::new(Observation::Nature(nature), 50, 1_000_000_000_000),
+        ];
+        assert_eq!(
+            identify_advance(&[SEED0], OffsetType::BW2Continue, &steps),
+            Err(ConfigError::ToleranceTooLarge { tolerance: 1_000_000_000_000, max: MAX_TOLERANCE })
+        );
+        assert!(identify_advance(&[SEED0], OffsetType::BW2Continue, &steps[..1]).is_ok());
+    }
+}
@@ -3,49 +3,16 @@ pub mod TID_impl;
 pub mod nature;
 pub mod grotto;
 pub mod wild_poke;
+pub mod algebra;
+pub mod identify;
+pub mod needle;
 pub use offset_impl::OffsetType;
+pub use algebra::{distance, lcg_advance, lcg_retreat};
 
 // lcg定数
 const LCG_MULTIPLIER: u64 = 0x5D588B656C078965u64;
 const LCG_INCREMENT: u64 = 0x269EC3u64;
 
-#[derive(Clone, Copy)]
-struct Mat {
-    a11: u64, a12: u64,
-    a21: u64, a22: u64,
-}
-
-impl Mat {
-    fn mul(self, rhs: Mat) -> Mat {
-        Mat {
-            a11: self.a11.wrapping_mul(rhs.a11)
-                .wrapping_add(self.a12.wrapping_mul(rhs.a21)),
-            a12: self.a11.wrapping_mul(rhs.a12)
-                .wrapping_add(self.a12.wrapping_mul(rhs.a22)),
-            a21: self.a21.wrapping_mul(rhs.a11)
-                .wrapping_add(self.a22.wrapping_mul(rhs.a21)),
-            a22: self.a21.wrapping_mul(rhs.a12)
-                .wrapping_add(self.a22.wrapping_mul(rhs.a22)),
-        }
-    }
-}
-
-fn mat_pow(mut base: Mat, mut exp: u64) -> Mat {
-    let mut result = Mat {
-        a11: 1, a12: 0,
-        a21: 0, a22: 1,
-    };
-
-    while exp > 0 {
-        if exp & 1 == 1 {
-            result = result.mul(base);
-        }
-        base = base.mul(base);
-        exp >>= 1;
-    }
-    result
-}
-
 #[derive(Clone, Copy, Debug)]
 pub struct Lcg {
     pub state: u64,
@@ -62,30 +29,28 @@ impl Lcg {
 
     pub fn next(&mut self) -> u64 {
         self.state = self.state.wrapping_mul(LCG_MULTIPLIER).wrapping_add(LCG_INCREMENT);
-        self.step += 1;
+        self.step = self.step.wrapping_add(1);
         self.state
     }
     
     pub fn advance(&mut self, n: u64) -> u64 {
-        if n == 0 {
-            return self.state;
-        }
-
-        let (mul, add) = Lcg::lcg_advance_params(LCG_MULTIPLIER, LCG_INCREMENT, n);
-        self.state = self.state.wrapping_mul(mul).wrapping_add(add);
-        self.step += n;
+        self.state = algebra::lcg_advance(self.state, n);
+        self.step = self.step.wrapping_add(n);
         self.state
     }
 
-    /// x_n = mul * x_0 + add
-    fn lcg_advance_params(a: u64, c: u64, n: u64) -> (u64, u64) {
-        let m = Mat {
-            a11: a, a12: c,
-            a21: 0, a22: 1,
-        };
+    /// 1 つ前の状態に戻す
+    pub fn prev(&mut self) -> u64 {
+        self.state = lcg_prev(self.state);
+        self.step = self.step.wrapping_sub(1);
+        self.state
+    }
 
-        let p = mat_pow(m, n);
-        (p.a11, p.a12)
+    /// n 回戻す
+    pub fn retreat(&mut self, n: u64) -> u64 {
+        self.state = algebra::lcg_retreat(self.state, n);
+        self.step = self.step.wrapping_sub(n);
+        self.state
     }
 
     pub fn rand(&mut self, max: u64) -> u32 {
@@ -101,6 +66,10 @@ pub const fn lcg_next(seed: u64) -> u64 {
     seed.wrapping_mul(LCG_MULTIPLIER).wrapping_add(LCG_INCREMENT)
 }
 
+pub const fn lcg_prev(seed: u64) -> u64 {
+    seed.wrapping_mul(algebra::LCG_MULTIPLIER_INV).wrapping_add(algebra::LCG_INCREMENT_INV)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,7 @@
+use std::str::FromStr;
+
 use super::Lcg;
+use crate::error::ConfigError;
 
 #[derive(Debug,Clone,PartialEq,Eq)]
 pub struct Nature(u8);
@@ -19,6 +22,13 @@ impl Nature {
         Nature(v)
     }
 
+    pub fn try_new(v: u8) -> Result<Self, ConfigError> {
+        if v >= Self::MAX {
+            return Err(ConfigError::InvalidNature(v));
+        }
+        Ok(Nature(v))
+    }
+
     pub fn id(&self) -> u8 {
         self.0
     }
@@ -26,6 +36,29 @@ impl Nature {
     pub fn name(&self) -> &'static str {
         NATURE_NAMES[self.0 as usize]
     }
+
+    /// 英語名から引く (大文字小文字は区別しない)
+    pub fn from_name(name: &str) -> Option<Self> {
+        NATURE_NAMES
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(name.trim()))
+            .map(|i| Nature(i as u8))
+    }
+}
+
+/// 英語名か 0-24 の ID を受け付ける
+impl FromStr for Nature {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(n) = Nature::from_name(s) {
+            return Ok(n);
+        }
+        match s.trim().parse::<u8>() {
+            Ok(id) => Nature::try_new(id),
+            Err(_) => Err(ConfigError::UnknownNature(s.trim().to_string())),
+        }
+    }
 }
 
 impl Lcg {
@@ -45,4 +78,13 @@ mod tests {
         let nature = seed.get_nature();
         assert_eq!(nature, Nature(4));
     }
+
+    #[test]
+    fn parse_name_or_id() {
+        assert_eq!("naughty".parse::<Nature>(), Ok(Nature(4)));
+        assert_eq!(" Quirky ".parse::<Nature>(), Ok(Nature(24)));
+        assert_eq!("19".parse::<Nature>(), Ok(Nature(19)));
+        assert_eq!("25".parse::<Nature>(), Err(ConfigError::InvalidNature(25)));
+        assert!("spicy".parse::<Nature>().is_err());
+    }
 }
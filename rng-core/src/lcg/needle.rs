@@ -0,0 +1,211 @@
+/*!
+レポートの針
+
+セーブ画面のレポートに出る針の向きは、その時点の LCG の次の値を 8 方向にしたもの
+レポートを開くたびに 1 消費するので、何回か続けて開いた針の並びから今の消費数が分かる
+*/
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::{lcg_advance, Lcg};
+use crate::error::ConfigError;
+
+/// 針の向き。上から時計回りに 0-7
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Needle {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+const ARROWS: [char; 8] = ['↑', '↗', '→', '↘', '↓', '↙', '←', '↖'];
+const COMPASS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+impl Needle {
+    pub const ALL: [Needle; 8] = [
+        Needle::Up,
+        Needle::UpRight,
+        Needle::Right,
+        Needle::DownRight,
+        Needle::Down,
+        Needle::DownLeft,
+        Needle::Left,
+        Needle::UpLeft,
+    ];
+
+    /// 0-7 以外は 8 で割った余り
+    pub const fn from_id(id: u8) -> Self {
+        Self::ALL[(id % 8) as usize]
+    }
+
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn arrow(self) -> char {
+        ARROWS[self as usize]
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '0'..='7' => Some(Needle::from_id(c as u8 - b'0')),
+            _ => ARROWS.iter().position(|&a| a == c).map(|i| Needle::from_id(i as u8)),
+        }
+    }
+
+    /**
+    観測した針の並びを読む
+
+    数字 (`03725`)、矢印 (`↑→↘`)、方角 (`N,E,SE`) のどれでもよい
+    数字と矢印は1文字ずつ、方角はカンマか空白で区切る
+    */
+    pub fn parse_sequence(s: &str) -> Result<Vec<Needle>, ConfigError> {
+        let mut out = Vec::new();
+        for token in s.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+            if let Ok(needle) = token.parse::<Needle>() {
+                out.push(needle);
+                continue;
+            }
+            let chars: Option<Vec<Needle>> = token.chars().map(Needle::from_char).collect();
+            out.extend(chars.ok_or_else(|| ConfigError::InvalidNeedle(token.to_string()))?);
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for Needle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+/// 0-7、矢印1文字、方角 (`N` `NE` ... `NW`、大文字小文字は区別しない) のどれか1つ
+impl FromStr for Needle {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(i) = COMPASS.iter().position(|c| c.eq_ignore_ascii_case(s)) {
+            return Ok(Needle::from_id(i as u8));
+        }
+        let mut chars = s.chars();
+        match (chars.next().and_then(Needle::from_char), chars.next()) {
+            (Some(needle), None) => Ok(needle),
+            _ => Err(ConfigError::InvalidNeedle(s.to_string())),
+        }
+    }
+}
+
+impl Lcg {
+    /// 今の状態で開いたレポートの針 (1 消費する)
+    pub fn get_needle(&mut self) -> Needle {
+        Needle::from_id(self.rand(8) as u8)
+    }
+
+    /// 今の状態から `count` 回続けてレポートを開いたときの針 (自分は進めない)
+    pub fn needles(&self, count: usize) -> Vec<Needle> {
+        let mut lcg = *self;
+        (0..count).map(|_| lcg.get_needle()).collect()
+    }
+}
+
+/// `seed0` から `advances` 消費したそれぞれの時点で開いた針
+pub fn needle_sequence(seed0: u64, advances: Range<u64>) -> Vec<Needle> {
+    let start = Lcg { state: lcg_advance(seed0, advances.start), step: advances.start };
+    start.needles(advances.end.saturating_sub(advances.start) as usize)
+}
+
+/// 針の並びが合った位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeedleMatch {
+    pub seed0: u64,
+    /// 最初の針を開いた時点の消費数
+    pub advance: u64,
+    /// 観測した針をすべて開いた後の消費数
+    pub current: u64,
+}
+
+/**
+`seeds` それぞれについて、最初の針を `advances` の範囲で開いたとして `observed` と合う位置を探す
+
+見つかった順 (seed の順、その中では消費数の順) に返す
+範囲全体の針は持たず、観測と同じ長さの窓をずらしながら比べる
+*/
+pub fn find_needle_advances(seeds: &[u64], advances: Range<u64>, observed: &[Needle]) -> Vec<NeedleMatch> {
+    if observed.is_empty() || advances.is_empty() {
+        return Vec::new();
+    }
+    let len = observed.len() as u64;
+    let mut out = Vec::new();
+    for &seed0 in seeds {
+        let mut lcg = Lcg { state: lcg_advance(seed0, advances.start), step: advances.start };
+        let mut window: VecDeque<Needle> = (0..len).map(|_| lcg.get_needle()).collect();
+        for advance in advances.clone() {
+            if window.iter().eq(observed) {
+                // 周期 2^64 なので消費数も 2^64 で一周させる
+                out.push(NeedleMatch { seed0, advance, current: advance.wrapping_add(len) });
+            }
+            window.pop_front();
+            window.push_back(lcg.get_needle());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED0: u64 = 0x9B3E7C4BC185AE31;
+
+    #[test]
+    fn needles_match_repeated_rand() {
+        let mut lcg = Lcg::new(SEED0);
+        let expected: Vec<Needle> = (0..300).map(|_| Needle::from_id(lcg.rand(8) as u8)).collect();
+        assert_eq!(needle_sequence(SEED0, 0..300), expected);
+        assert_eq!(needle_sequence(SEED0, 120..180), expected[120..180]);
+        // 1 回目の針は seed1 の上位ビットで決まる
+        assert_eq!(expected[0].id() as u64, ((lcg_advance(SEED0, 1) >> 32) * 8) >> 32);
+    }
+
+    #[test]
+    fn parse_digits_arrows_and_compass() {
+        let expected = vec![Needle::Up, Needle::DownRight, Needle::Left];
+        assert_eq!(Needle::parse_sequence("036").unwrap(), expected);
+        assert_eq!(Needle::parse_sequence("↑↘←").unwrap(), expected);
+        assert_eq!(Needle::parse_sequence("n, se w").unwrap(), expected);
+        assert_eq!(Needle::parse_sequence("0 3,6").unwrap(), expected);
+        assert!(Needle::parse_sequence("08").is_err());
+        assert!(Needle::parse_sequence("up").is_err());
+        assert_eq!(expected.iter().map(|n| n.to_string()).collect::<String>(), "↑↘←");
+    }
+
+    #[test]
+    fn find_locates_the_observed_window() {
+        let observed = needle_sequence(SEED0, 57..69);
+        let found = find_needle_advances(&[0x113E10468C85C156, SEED0], 30..100, &observed);
+        assert!(found.contains(&NeedleMatch { seed0: SEED0, advance: 57, current: 69 }));
+        assert!(found.iter().all(|m| needle_sequence(m.seed0, m.advance..m.current) == observed));
+
+        // 範囲の端でもよい
+        let found = find_needle_advances(&[SEED0], 57..58, &observed);
+        assert_eq!(found, [NeedleMatch { seed0: SEED0, advance: 57, current: 69 }]);
+        assert!(find_needle_advances(&[SEED0], 58..100, &observed).iter().all(|m| m.advance != 57));
+    }
+
+    #[test]
+    fn find_wraps_at_the_end_of_the_period() {
+        let start = u64::MAX - 3;
+        let observed = Lcg { state: lcg_advance(SEED0, start), step: start }.needles(6);
+        let found = find_needle_advances(&[SEED0], start..u64::MAX, &observed);
+        assert!(found.contains(&NeedleMatch { seed0: SEED0, advance: start, current: 2 }));
+    }
+}
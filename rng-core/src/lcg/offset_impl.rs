@@ -1,4 +1,5 @@
 use super::Lcg;
+use OffsetStep::{Advance, Extra, Pt};
 
 const PROBABILITY_TABLE: [[u8; 5]; 6] = [
     [50, 100, 100, 100, 100],
@@ -9,7 +10,20 @@ const PROBABILITY_TABLE: [[u8; 5]; 6] = [
     [100, 100, 100, 100, 100],
 ];
 
-#[derive(Clone, Copy, Debug)]
+/**
+起動からの消費の手順
+
+`pt(n)` は確率テーブルを n 回、`Advance(n)` はただ n 消費、`Extra` は BW2 の続きからの追加消費
+手順は [`OffsetType::steps`] の表にまとめてあり、表にない起動方法は [`Lcg::offset_steps`] に直接渡せる
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffsetStep {
+    Pt(u32),
+    Advance(u64),
+    Extra,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum OffsetType {
     Bw1Start,
     Bw1Continue,
@@ -19,60 +33,56 @@ pub enum OffsetType {
     BW2ContinueWithLink,
 }
 
+impl OffsetType {
+    pub const ALL: [OffsetType; 6] = [
+        OffsetType::Bw1Start,
+        OffsetType::Bw1Continue,
+        OffsetType::BW2Start,
+        OffsetType::BW2StartChallengeMode,
+        OffsetType::BW2Continue,
+        OffsetType::BW2ContinueWithLink,
+    ];
+
+    pub const fn is_bw1(self) -> bool {
+        matches!(self, OffsetType::Bw1Start | OffsetType::Bw1Continue)
+    }
+
+    /// seed1 からの消費の手順
+    pub const fn steps(self) -> &'static [OffsetStep] {
+        match self {
+            // 以降は TID, SID
+            OffsetType::Bw1Start => &[Pt(3), Advance(3)],
+            OffsetType::Bw1Continue => &[Pt(5)],
+            // 最後の 1 つはチラチーノ用 pid。以降は TID, SID
+            OffsetType::BW2Start => &[Pt(1), Advance(2), Pt(1), Advance(4), Pt(1), Advance(3), Advance(1)],
+            OffsetType::BW2StartChallengeMode => &[Pt(1), Advance(1), Pt(1), Advance(2), Pt(1), Advance(2)],
+            OffsetType::BW2Continue => &[Pt(1), Advance(3), Pt(4), Extra],
+            OffsetType::BW2ContinueWithLink => &[Pt(1), Advance(2), Pt(4), Extra],
+        }
+    }
+}
+
 impl Lcg {
     pub fn offset_seed0(&mut self, offset_type: OffsetType) -> u64 {
         self.next();
         self.offset_seed1(offset_type)
     }
-    
+
     pub fn offset_seed1(&mut self, offset_type: OffsetType) -> u64 {
-        match offset_type {
-            // BW1はじめから
-            OffsetType::Bw1Start => {
-                self.pt(3);
-                self.advance(3);
-                // 以降はTID,SID
-            },
-            // BW1続きから
-            OffsetType::Bw1Continue => {
-                self.pt(5);
-            },
-            // BW2はじめから
-            OffsetType::BW2Start => {
-                self.pt(1);
-                self.advance(2);
-                self.pt(1);
-                self.advance(4);
-                self.pt(1);
-                self.advance(3);
-                // チラチーノ用pid
-                self.next();
-                // 以降はTID,SID
-            },
-            OffsetType::BW2StartChallengeMode => {
-                self.pt(1);
-                self.advance(1);
-                self.pt(1);
-                self.advance(2);
-                self.pt(1);
-                self.advance(2);
+        self.offset_steps(offset_type.steps())
+    }
+
+    /// `steps` の順に消費して、seed0 からの消費数を返す
+    pub fn offset_steps(&mut self, steps: &[OffsetStep]) -> u64 {
+        for step in steps {
+            match *step {
+                Pt(counts) => self.pt(counts),
+                Advance(n) => {
+                    self.advance(n);
+                }
+                Extra => self.offset_extra(),
             }
-            // BW2続きから
-            OffsetType::BW2Continue => {
-                self.pt(1);
-                self.advance(3);
-                self.pt(4);
-                self.offset_extra();
-            },
-            // BW2続きから(おもいでリンクあり)
-            OffsetType::BW2ContinueWithLink => {
-                self.pt(1);
-                self.advance(2);
-                self.pt(4);
-                self.offset_extra();
-            },
         }
-        
         self.step
     }
 
@@ -120,7 +130,7 @@ mod tests {
     fn test_offset_bw1_start() {
         let mut seed = Lcg::new(0x48B96278DC6233AB);
         let offset = seed.offset_seed1(OffsetType::Bw1Start);
-        let (_tid, _sid) = seed.tid_sid(OffsetType::Bw1Start);
+        let (_tid, _sid) = seed.tid_sid(OffsetType::Bw1Start).unwrap();
         assert_eq!(offset, 34);
     }
 
@@ -128,7 +138,68 @@ mod tests {
     fn test_bw2_tid() {
         let mut rng = Lcg::new(0x57BC6AEC7078A132);
         rng.offset_seed0(OffsetType::BW2Start);
-        let (tid, _sid) = rng.tid_sid(OffsetType::BW2Start);
+        let (tid, _sid) = rng.tid_sid(OffsetType::BW2Start).unwrap();
         assert_eq!(tid, 27754);
     }
-}
\ No newline at end of file
+
+    /// 表にする前の match の手順と同じ消費になる
+    #[test]
+    fn table_matches_the_old_sequences() {
+        let old = |lcg: &mut Lcg, t: OffsetType| match t {
+            OffsetType::Bw1Start => {
+                lcg.pt(3);
+                lcg.advance(3);
+            }
+            OffsetType::Bw1Continue => lcg.pt(5),
+            OffsetType::BW2Start => {
+                lcg.pt(1);
+                lcg.advance(2);
+                lcg.pt(1);
+                lcg.advance(4);
+                lcg.pt(1);
+                lcg.advance(3);
+                lcg.next();
+            }
+            OffsetType::BW2StartChallengeMode => {
+                lcg.pt(1);
+                lcg.advance(1);
+                lcg.pt(1);
+                lcg.advance(2);
+                lcg.pt(1);
+                lcg.advance(2);
+            }
+            OffsetType::BW2Continue => {
+                lcg.pt(1);
+                lcg.advance(3);
+                lcg.pt(4);
+                lcg.offset_extra();
+            }
+            OffsetType::BW2ContinueWithLink => {
+                lcg.pt(1);
+                lcg.advance(2);
+                lcg.pt(4);
+                lcg.offset_extra();
+            }
+        };
+        let mut seeds = Lcg::new(0x9B3E7C4BC185AE31);
+        for _ in 0..200 {
+            let seed1 = seeds.next();
+            for t in OffsetType::ALL {
+                let mut expected = Lcg::new(seed1);
+                old(&mut expected, t);
+                let mut lcg = Lcg::new(seed1);
+                assert_eq!(lcg.offset_seed1(t), expected.step, "{t:?}");
+                assert_eq!(lcg.state, expected.state);
+            }
+        }
+    }
+
+    #[test]
+    fn custom_steps() {
+        let mut a = Lcg::new(0x490CC591E17E7DB7);
+        let mut b = Lcg::new(0x490CC591E17E7DB7);
+        assert_eq!(a.offset_steps(OffsetType::BW2Continue.steps()), b.offset_seed1(OffsetType::BW2Continue));
+        let mut c = Lcg::new(0x490CC591E17E7DB7);
+        assert_eq!(c.offset_steps(&[OffsetStep::Advance(7)]), 7);
+    }
+}
@@ -28,13 +28,7 @@ impl Lcg {
         if lcg_local.rand(100) > 9 {
             return WildPoke::default()
         }
-        let mut result = WildPoke::default();
-        result.slot = Some(lcg_local.rand(100));
-        lcg_local.next();
-        result.poke_code = Some((lcg_local.next() >> 32) as u32);
-        result.nature = Some(lcg_local.get_nature());
-        result.item = Some(lcg_local.rand(100));
-        result
+        lcg_local.draw_wild_poke()
     }
 
     pub fn get_wild_poke_bw2(&mut self) -> WildPoke {
@@ -42,13 +36,19 @@ impl Lcg {
         if lcg_local.rand(100) > 20 {
             return WildPoke::default()
         }
-        let mut result = WildPoke::default();
-        result.slot = Some(lcg_local.rand(100));
-        lcg_local.next();
-        result.poke_code = Some((lcg_local.next() >> 32) as u32);
-        result.nature = Some(lcg_local.get_nature());
-        result.item = Some(lcg_local.rand(100));
-        result
+        lcg_local.draw_wild_poke()
+    }
+
+    /// 出現が決まった後の スロット → (1 つ飛ばし) → PID → 性格 → 持ち物 を引く (自分も進める)
+    fn draw_wild_poke(&mut self) -> WildPoke {
+        let slot = self.rand(100);
+        self.next();
+        WildPoke {
+            slot: Some(slot),
+            poke_code: Some((self.next() >> 32) as u32),
+            nature: Some(self.get_nature()),
+            item: Some(self.rand(100)),
+        }
     }
 }
 
@@ -1,5 +1,7 @@
+pub mod error;
 pub mod models;
 pub mod mt;
+pub mod iv_signature;
 pub mod lcg;
 pub mod initial_seed;
 pub mod sha_1;
@@ -0,0 +1,328 @@
+/*!
+検索する日付の集合
+
+範囲・日付の一覧・季節・曜日を組み合わせて作る。文字列からは `FromStr` で読める
+
+```text
+2033-08..2035-01,!summer        2033年8月から2035年1月まで、夏を除く
+2040,autumn,winter,sat          2040年の秋と冬の土曜日
+2012-12-31,!sun                 2012年12月31日 (日曜なら除く)
+```
+*/
+
+use std::str::FromStr;
+
+use crate::error::ConfigError;
+use crate::models::ds_date_time::days_in_month;
+use crate::models::game_date::GameDate;
+
+/**
+BW の季節
+
+ゲーム内の季節は実際の月ではなく月を 4 で割った余りで決まり、1 年で 3 周する
+(1月が春、2月が夏、3月が秋、4月が冬、5月がまた春 ...)
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const fn of_month(month: u8) -> Season {
+        match month % 4 {
+            1 => Season::Spring,
+            2 => Season::Summer,
+            3 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Season> {
+        match s.to_ascii_lowercase().as_str() {
+            "spring" => Some(Season::Spring),
+            "summer" => Some(Season::Summer),
+            "autumn" | "fall" => Some(Season::Autumn),
+            "winter" => Some(Season::Winter),
+            _ => None,
+        }
+    }
+}
+
+/// 0 = 日曜日 ... 6 = 土曜日 (`GameDate::weekday` と同じ)
+fn weekday_from_name(s: &str) -> Option<u8> {
+    const NAMES: [&str; 7] = ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"];
+    let lower = s.to_ascii_lowercase();
+    NAMES
+        .iter()
+        .position(|n| *n == lower || (lower.len() == 3 && n.starts_with(&lower)))
+        .map(|i| i as u8)
+}
+
+/// 日付の集合
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateSet {
+    /// 2000-01-01 から 2099-12-31 まで
+    All,
+    /// 両端を含む範囲 (月や年をまたいでもよい)
+    Range(GameDate, GameDate),
+    /// 日付の一覧
+    List(Vec<GameDate>),
+    /// 毎年の決まった月日
+    MonthDays(Vec<(u8, u8)>),
+    Season(Season),
+    /// 0 = 日曜日 ... 6 = 土曜日
+    Weekday(u8),
+    Union(Vec<DateSet>),
+    Intersection(Vec<DateSet>),
+    /// 左から右を除いたもの
+    Difference(Box<DateSet>, Box<DateSet>),
+}
+
+impl DateSet {
+    pub fn all() -> Self {
+        DateSet::All
+    }
+
+    /// `start` から `end` まで (両端を含む)
+    pub fn range(start: GameDate, end: GameDate) -> Self {
+        DateSet::Range(start, end)
+    }
+
+    pub fn list(dates: impl IntoIterator<Item = GameDate>) -> Self {
+        DateSet::List(dates.into_iter().collect())
+    }
+
+    pub fn month_days(month_days: &[(u8, u8)]) -> Self {
+        DateSet::MonthDays(month_days.to_vec())
+    }
+
+    pub fn season(season: Season) -> Self {
+        DateSet::Season(season)
+    }
+
+    pub fn weekday(weekday: u8) -> Self {
+        DateSet::Weekday(weekday)
+    }
+
+    pub fn union(self, other: DateSet) -> Self {
+        match self {
+            DateSet::Union(mut sets) => {
+                sets.push(other);
+                DateSet::Union(sets)
+            }
+            set => DateSet::Union(vec![set, other]),
+        }
+    }
+
+    pub fn intersect(self, other: DateSet) -> Self {
+        match self {
+            DateSet::Intersection(mut sets) => {
+                sets.push(other);
+                DateSet::Intersection(sets)
+            }
+            set => DateSet::Intersection(vec![set, other]),
+        }
+    }
+
+    pub fn except(self, other: DateSet) -> Self {
+        DateSet::Difference(Box::new(self), Box::new(other))
+    }
+
+    pub fn contains(&self, date: &GameDate) -> bool {
+        match self {
+            DateSet::All => true,
+            DateSet::Range(start, end) => key(start) <= key(date) && key(date) <= key(end),
+            DateSet::List(dates) => dates.contains(date),
+            DateSet::MonthDays(md) => md.contains(&(date.month(), date.day())),
+            DateSet::Season(season) => Season::of_month(date.month()) == *season,
+            DateSet::Weekday(w) => date.weekday() == *w,
+            DateSet::Union(sets) => sets.iter().any(|s| s.contains(date)),
+            DateSet::Intersection(sets) => sets.iter().all(|s| s.contains(date)),
+            DateSet::Difference(a, b) => a.contains(date) && !b.contains(date),
+        }
+    }
+
+    /// 集合に入る日付を古い順に返す
+    pub fn dates(&self) -> Vec<GameDate> {
+        all_dates().filter(|d| self.contains(d)).collect()
+    }
+}
+
+fn key(d: &GameDate) -> (u8, u8, u8) {
+    (d.year(), d.month(), d.day())
+}
+
+fn all_dates() -> impl Iterator<Item = GameDate> {
+    (0..100u8).flat_map(|y| {
+        (1..=12u8).flat_map(move |m| (1..=days_in_month(y, m)).map(move |d| GameDate::new(y, m, d)))
+    })
+}
+
+/// 年は 2000-2099 か下2桁
+fn parse_year(s: &str) -> Option<u8> {
+    let y: u32 = s.parse().ok()?;
+    match (s.len(), y) {
+        (4, 2000..=2099) => Some((y - 2000) as u8),
+        (1 | 2, 0..=99) => Some(y as u8),
+        _ => None,
+    }
+}
+
+/// "2033" / "2033-08" / "2033-08-27" を、それが表す最初と最後の日にする
+fn parse_period(s: &str) -> Option<(GameDate, GameDate)> {
+    let parts: Vec<&str> = s.split('-').map(str::trim).collect();
+    let year = parse_year(parts.first()?)?;
+    let field = |i: usize| parts[i].parse::<u8>().ok();
+    match parts.len() {
+        1 => Some((GameDate::new(year, 1, 1), GameDate::new(year, 12, 31))),
+        2 => {
+            let month = field(1)?;
+            let last = days_in_month(year, month);
+            (last > 0).then(|| (GameDate::new(year, month, 1), GameDate::new(year, month, last)))
+        }
+        3 => {
+            let date = GameDate::try_new(year, field(1)?, field(2)?).ok()?;
+            Some((date, date))
+        }
+        _ => None,
+    }
+}
+
+/**
+カンマ区切りの項目を読む。`!` を付けた項目は除外
+
+- 日付 `2033-08-27`、月 `2033-08`、年 `2033` と、それらを `..` でつないだ範囲 (年は下2桁でもよい)
+- 季節 `spring` `summer` `autumn` (`fall`) `winter`
+- 曜日 `sun` ... `sat` (`sunday` なども可)
+
+日付・季節・曜日はそれぞれの種類の中では和集合、種類どうしは積集合になる
+("2040,autumn,sat" は 2040年の秋の土曜日)。日付の項目がなければ全期間
+*/
+impl FromStr for DateSet {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |term: &str| ConfigError::InvalidDateSet(term.to_string());
+        let mut periods = Vec::new();
+        let mut seasons = Vec::new();
+        let mut weekdays = Vec::new();
+        let mut excluded = Vec::new();
+
+        for raw in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (negated, term) = match raw.strip_prefix('!') {
+                Some(rest) => (true, rest.trim()),
+                None => (false, raw),
+            };
+            let set = if let Some(season) = Season::from_name(term) {
+                DateSet::Season(season)
+            } else if let Some(w) = weekday_from_name(term) {
+                DateSet::Weekday(w)
+            } else if let Some((a, b)) = term.split_once("..") {
+                let (start, _) = parse_period(a.trim()).ok_or_else(|| invalid(raw))?;
+                let (_, end) = parse_period(b.trim()).ok_or_else(|| invalid(raw))?;
+                if key(&start) > key(&end) {
+                    return Err(invalid(raw));
+                }
+                DateSet::Range(start, end)
+            } else {
+                let (start, end) = parse_period(term).ok_or_else(|| invalid(raw))?;
+                DateSet::Range(start, end)
+            };
+
+            if negated {
+                excluded.push(set);
+                continue;
+            }
+            match set {
+                DateSet::Season(_) => seasons.push(set),
+                DateSet::Weekday(_) => weekdays.push(set),
+                _ => periods.push(set),
+            }
+        }
+
+        let mut result = DateSet::All;
+        for group in [periods, seasons, weekdays] {
+            if !group.is_empty() {
+                result = result.intersect(DateSet::Union(group));
+            }
+        }
+        for set in excluded {
+            result = result.except(set);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Vec<GameDate> {
+        s.parse::<DateSet>().unwrap().dates()
+    }
+
+    #[test]
+    fn season_follows_month_mod_4() {
+        let months: Vec<u8> = (1..=12).filter(|&m| Season::of_month(m) == Season::Summer).collect();
+        assert_eq!(months, [2, 6, 10]);
+        assert_eq!(Season::of_month(4), Season::Winter);
+        assert_eq!(Season::of_month(9), Season::Spring);
+    }
+
+    #[test]
+    fn range_crosses_month_and_year() {
+        let dates = DateSet::range(GameDate::new(33, 12, 30), GameDate::new(34, 1, 2)).dates();
+        assert_eq!(
+            dates,
+            [
+                GameDate::new(33, 12, 30),
+                GameDate::new(33, 12, 31),
+                GameDate::new(34, 1, 1),
+                GameDate::new(34, 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn builders_compose() {
+        let set = DateSet::all()
+            .except(DateSet::season(Season::Summer))
+            .intersect(DateSet::weekday(6))
+            .intersect(DateSet::range(GameDate::new(26, 1, 1), GameDate::new(26, 2, 28)));
+        let dates = set.dates();
+        assert!(dates.iter().all(|d| d.month() == 1 && d.weekday() == 6));
+        assert_eq!(dates.len(), 5);
+
+        let pup = DateSet::month_days(&[(4, 29), (12, 31)]).union(DateSet::list([GameDate::new(0, 2, 29)]));
+        assert_eq!(pup.dates().len(), 201);
+    }
+
+    #[test]
+    fn parse_cli_syntax() {
+        let dates = parse("2033-08..2035-01,!summer");
+        assert_eq!(dates.first(), Some(&GameDate::new(33, 8, 1)));
+        assert_eq!(dates.last(), Some(&GameDate::new(35, 1, 31)));
+        assert!(dates.iter().all(|d| d.month() % 4 != 2));
+
+        let sats = parse("2040, autumn, winter, sat");
+        assert!(!sats.is_empty());
+        assert!(sats.iter().all(|d| {
+            d.year() == 40 && d.weekday() == 6 && matches!(Season::of_month(d.month()), Season::Autumn | Season::Winter)
+        }));
+
+        assert_eq!(parse("12-12-31"), [GameDate::new(12, 12, 31)]);
+        assert_eq!(parse("!summer").len(), DateSet::all().except(DateSet::season(Season::Summer)).dates().len());
+        assert_eq!(parse("2099-12-31..2099").len(), 1);
+        assert_eq!(parse("").len(), 36525);
+    }
+
+    #[test]
+    fn parse_rejects_bad_terms() {
+        for s in ["2100", "2033-13", "2033-02-30", "2035..2033", "someday", "2033-08..", "monsoon"] {
+            assert!(s.parse::<DateSet>().is_err(), "{s}");
+        }
+    }
+}
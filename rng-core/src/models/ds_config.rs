@@ -1,10 +1,52 @@
-use crate::models::{VersionConfig, game_version::GameVersion};
-use serde::{Deserialize, Deserializer, Serialize};
+use crate::error::ConfigError;
+use crate::models::{VersionConfig, game_version::GameVersion, hardware::Hardware};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MAC_MAX: u64 = (1 << 48) - 1;
+
+/// "00:09:BF:6D:93:CE" / "00-09-BF-6D-93-CE" のように 6 バイトを区切ったもの
+fn parse_separated_mac(s: &str) -> Option<u64> {
+    let octets: Vec<&str> = s.split([':', '-']).collect();
+    if octets.len() != 6 || octets.iter().any(|o| o.is_empty() || o.len() > 2) {
+        return None;
+    }
+    octets
+        .iter()
+        .try_fold(0u64, |acc, o| u8::from_str_radix(o, 16).ok().map(|b| acc << 8 | b as u64))
+}
+
+/**
+MAC アドレスを読む
+
+`00:09:BF:6D:93:CE`、`00-09-BF-6D-93-CE`、`0x0009BF6D93CE`、`0009BF6D93CE` を受け付ける
+48 ビットに収まらなければエラー
+*/
+pub fn parse_mac(s: &str) -> Result<u64, ConfigError> {
+    let t = s.trim();
+    let invalid = || ConfigError::InvalidMac(t.to_string());
+    let mac = match parse_separated_mac(t) {
+        Some(mac) => mac,
+        None => {
+            let hex = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")).unwrap_or(t);
+            u64::from_str_radix(hex, 16).map_err(|_| invalid())?
+        }
+    };
+    if mac > MAC_MAX {
+        return Err(invalid());
+    }
+    Ok(mac)
+}
 
 fn parse_hex_or_decimal_u64(s: &str) -> Result<u64, String> {
     let s = s.trim();
-    if s.starts_with("0x") || s.starts_with("0X") {
+    if let Some(mac) = parse_separated_mac(s) {
+        Ok(mac)
+    } else if s.starts_with("0x") || s.starts_with("0X") {
         u64::from_str_radix(&s[2..], 16).map_err(|e| format!("invalid hex: {}", e))
     } else {
         s.parse::<u64>().map_err(|e| format!("invalid number: {}", e))
@@ -66,23 +108,63 @@ where
 
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "RawDSConfig")]
 pub struct DSConfig {
     #[serde(rename = "version")]
     pub Version: GameVersion,
-    #[serde(rename = "timer0", deserialize_with = "de_u16_hex_or_dec")]
+    #[serde(rename = "timer0", serialize_with = "ser_timer0")]
     pub Timer0: u16,
-    #[serde(rename = "is_dslite")]
-    pub IsDSLite: bool,
-    #[serde(rename = "mac", deserialize_with = "de_u64_hex_or_dec")]
+    #[serde(rename = "hardware")]
+    pub Hardware: Hardware,
+    #[serde(rename = "mac", serialize_with = "ser_mac")]
     pub MAC: u64,
 }
 
+/// 書き出すときは "0x10FA" の形にそろえる
+fn ser_timer0<S: Serializer>(v: &u16, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("0x{v:04X}"))
+}
+
+/// 書き出すときは "0x0009BF6D93CE" の形にそろえる
+fn ser_mac<S: Serializer>(v: &u64, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("0x{v:012X}"))
+}
+
+/// ds_config.json の1件。以前の `is_dslite` も読めるようにしておく
+#[derive(Deserialize)]
+struct RawDSConfig {
+    version: GameVersion,
+    #[serde(deserialize_with = "de_u16_hex_or_dec")]
+    timer0: u16,
+    #[serde(default)]
+    hardware: Option<Hardware>,
+    #[serde(default)]
+    is_dslite: Option<bool>,
+    #[serde(deserialize_with = "de_u64_hex_or_dec")]
+    mac: u64,
+}
+
+impl TryFrom<RawDSConfig> for DSConfig {
+    type Error = ConfigError;
+
+    fn try_from(raw: RawDSConfig) -> Result<Self, Self::Error> {
+        let hardware = match (raw.hardware, raw.is_dslite) {
+            (Some(h), None) => h,
+            (None, Some(lite)) => Hardware::from_is_dslite(lite),
+            (Some(h), Some(lite)) if h == Hardware::from_is_dslite(lite) => h,
+            (Some(h), Some(lite)) => return Err(ConfigError::HardwareConflict { hardware: h, is_dslite: lite }),
+            (None, None) => return Err(ConfigError::MissingHardware),
+        };
+        Ok(DSConfig::new(raw.version, raw.timer0, hardware, raw.mac))
+    }
+}
+
 impl DSConfig {
-    pub fn new(version: GameVersion, timer0: u16, is_dslite: bool, mac: u64) -> Self {
+    pub fn new(version: GameVersion, timer0: u16, hardware: Hardware, mac: u64) -> Self {
         Self {
             Version: version,
             Timer0: timer0,
-            IsDSLite: is_dslite,
+            Hardware: hardware,
             MAC: mac,
         }
     }
@@ -90,6 +172,51 @@ impl DSConfig {
     pub fn get_version_config(&self) -> VersionConfig{
         VersionConfig::from_version(self.Version)
     }
+
+    /// MAC が 48 ビットに収まり、Timer0 がバージョンの目安の範囲にあるか。問題がなければ空
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut problems = Vec::new();
+        if self.MAC > MAC_MAX {
+            problems.push(ConfigError::InvalidMac(format!("0x{:X}", self.MAC)));
+        }
+        let range = self.Version.typical_timer0();
+        if !range.contains(&self.Timer0) {
+            problems.push(ConfigError::ImplausibleTimer0 {
+                timer0: self.Timer0,
+                min: *range.start(),
+                max: *range.end(),
+            });
+        }
+        problems
+    }
+}
+
+/// ds_config.json 全体。プロファイルは名前順に並べる
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DsConfigFile {
+    pub ds_configs: BTreeMap<String, DSConfig>,
+}
+
+impl DsConfigFile {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 2 スペースでインデントして書き出す (hex の表記はそろう)
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<DSConfig> {
+        self.ds_configs.get(name).copied()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.ds_configs.keys().map(String::as_str)
+    }
 }
 
 #[cfg(test)]
@@ -114,21 +241,94 @@ mod tests {
 
     #[test]
     fn test_dsconfig_new_and_fields() {
-        let cfg = DSConfig::new(GameVersion::Black, 0x10FA, true, 0x1234_ABCDu64);
+        let cfg = DSConfig::new(GameVersion::Black, 0x10FA, Hardware::DsLite, 0x1234_ABCDu64);
         assert_eq!(cfg.Version, GameVersion::Black);
         assert_eq!(cfg.Timer0, 0x10FA);
-        assert!(cfg.IsDSLite);
+        assert_eq!(cfg.Hardware, Hardware::DsLite);
         assert_eq!(cfg.MAC, 0x1234_ABCDu64);
     }
 
     #[test]
     fn test_serde_roundtrip() {
-        let cfg = DSConfig::new(GameVersion::White2, 0x10FA, false, 0xDEAD_BEEFu64);
+        let cfg = DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsLite, 0xDEAD_BEEFu64);
         let s = serde_json::to_string(&cfg).expect("serialize");
         let de: DSConfig = serde_json::from_str(&s).expect("deserialize");
         assert_eq!(de.MAC, cfg.MAC);
         assert_eq!(de.Timer0, cfg.Timer0);
-        assert_eq!(de.IsDSLite, cfg.IsDSLite);
+        assert_eq!(de.Hardware, cfg.Hardware);
         assert_eq!(de.Version, cfg.Version);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_hardware_and_legacy_is_dslite() {
+        let j = r#"{ "version": "White", "timer0": "0xC68", "is_dslite": true, "mac": 1 }"#;
+        let cfg: DSConfig = serde_json::from_str(j).expect("legacy is_dslite");
+        assert_eq!(cfg.Hardware, Hardware::DsLite);
+
+        let j = r#"{ "version": "White2", "timer0": "0x10FA", "hardware": "ds", "mac": 1 }"#;
+        let cfg: DSConfig = serde_json::from_str(j).expect("hardware");
+        assert_eq!(cfg.Hardware, Hardware::DsPhat);
+        let unsupported = r#"{ "version": "White2", "timer0": "0x1270", "hardware": "3ds", "mac": 1 }"#;
+        assert!(serde_json::from_str::<DSConfig>(unsupported).is_err());
+
+        let both = r#"{ "version": "White2", "timer0": 1, "hardware": "ds", "is_dslite": false, "mac": 1 }"#;
+        assert_eq!(serde_json::from_str::<DSConfig>(both).unwrap().Hardware, Hardware::DsPhat);
+        for conflict in [
+            r#"{ "version": "White2", "timer0": 1, "hardware": "ds", "is_dslite": true, "mac": 1 }"#,
+            r#"{ "version": "White2", "timer0": 1, "hardware": "ds_lite", "is_dslite": false, "mac": 1 }"#,
+        ] {
+            let err = serde_json::from_str::<DSConfig>(conflict).unwrap_err();
+            assert!(err.to_string().contains("contradicts"), "{err}");
+        }
+        let same = r#"{ "version": "White2", "timer0": 1, "hardware": "ds_lite", "is_dslite": true, "mac": 1 }"#;
+        assert_eq!(serde_json::from_str::<DSConfig>(same).unwrap().Hardware, Hardware::DsLite);
+        let missing = r#"{ "version": "White2", "timer0": 1, "mac": 1 }"#;
+        assert!(serde_json::from_str::<DSConfig>(missing).is_err());
+    }
+
+    #[test]
+    fn test_parse_mac_forms() {
+        for s in ["00:09:BF:6D:93:CE", "00-09-bf-6d-93-ce", "0x0009bf6d93ce", "0009BF6D93CE", " 0:9:bf:6d:93:ce "] {
+            assert_eq!(parse_mac(s), Ok(0x0009_BF6D_93CE), "{s}");
+        }
+        for s in ["0x1000000000000", "00:09:BF:6D:93", "00:09:BF:6D:93:CE:01", "00:09:BF:6D:93:XY", ""] {
+            assert!(parse_mac(s).is_err(), "{s}");
+        }
+        let j = r#"{ "version": "White2", "timer0": "0x10FA", "hardware": "ds", "mac": "00:09:BF:6D:93:CE" }"#;
+        assert_eq!(serde_json::from_str::<DSConfig>(j).unwrap().MAC, 0x0009_BF6D_93CE);
+    }
+
+    #[test]
+    fn test_serialize_writes_hex() {
+        let cfg = DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsLite, 0x0009_BF6D_93CE);
+        let v = serde_json::to_value(cfg).unwrap();
+        assert_eq!(v["timer0"], "0x10FA");
+        assert_eq!(v["mac"], "0x0009BF6D93CE");
+        assert_eq!(v["hardware"], "ds_lite");
+        assert!(v.get("is_dslite").is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009_BF6D_93CE).validate().is_empty());
+        let bad = DSConfig::new(GameVersion::Black, 0x10FA, Hardware::DsPhat, 1 << 48);
+        let problems = bad.validate();
+        assert_eq!(problems.len(), 2);
+        assert!(matches!(problems[1], ConfigError::ImplausibleTimer0 { timer0: 0x10FA, .. }));
+    }
+
+    #[test]
+    fn test_config_file_roundtrip_is_sorted() {
+        let j = r#"{ "ds_configs": {
+            "zeta": { "version": "Black", "timer0": 3194, "is_dslite": false, "mac": 42 },
+            "alpha": { "version": "White2", "timer0": "0x10fa", "hardware": "ds_lite", "mac": "00:09:BF:6D:93:CE" }
+        } }"#;
+        let file: DsConfigFile = serde_json::from_str(j).unwrap();
+        assert_eq!(file.names().collect::<Vec<_>>(), ["alpha", "zeta"]);
+        let text = serde_json::to_string(&file).unwrap();
+        assert!(text.find("alpha").unwrap() < text.find("zeta").unwrap());
+        assert!(text.contains(r#""timer0":"0x0C7A""#) && text.contains(r#""mac":"0x00000000002A""#));
+        let again: DsConfigFile = serde_json::from_str(&text).unwrap();
+        assert_eq!(again.get("alpha").map(|c| c.MAC), Some(0x0009_BF6D_93CE));
+    }
+}
@@ -0,0 +1,347 @@
+/*!
+DS の時計の暦
+
+DS の RTC は 2000-2099 年を下2桁で持つ。この範囲では 4 で割り切れる年がすべて閏年 (2000 年も含む)
+SHA-1 に入る date8 / time9 は各フィールドを BCD にしたもの
+*/
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+
+use crate::error::ConfigError;
+use crate::models::GameTime;
+use crate::models::game_date::GameDate;
+
+/// 2000-01-01 は土曜日
+const WEEKDAY_OF_2000_01_01: u32 = 6;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// 2000-01-01 00:00:00 から 2100-01-01 00:00:00 までの秒数
+const SECONDS_IN_RANGE: i64 = (100 * 365 + 25) * SECONDS_PER_DAY;
+
+/// 平年の各月の前までの日数
+const DAYS_BEFORE_MONTH: [u16; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+pub const fn is_leap_year(year: u8) -> bool {
+    year.is_multiple_of(4)
+}
+
+/// 存在しない月なら 0
+pub const fn days_in_month(year: u8, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// 2000-01-01 からの日数 (日付は正しいものとする)
+const fn days_since_2000(year: u8, month: u8, day: u8) -> u32 {
+    let y = year as u32;
+    let leap_day = if is_leap_year(year) && month > 2 { 1 } else { 0 };
+    365 * y + y.div_ceil(4) + DAYS_BEFORE_MONTH[month as usize - 1] as u32 + leap_day + day as u32 - 1
+}
+
+/// 0 = 日曜日, 1 = 月曜日, ..., 6 = 土曜日
+pub const fn weekday(year: u8, month: u8, day: u8) -> u8 {
+    ((days_since_2000(year, month, day) + WEEKDAY_OF_2000_01_01) % 7) as u8
+}
+
+/// 0-99 を BCD に
+pub const fn to_bcd(v: u8) -> u8 {
+    ((v / 10) << 4) | (v % 10)
+}
+
+/// BCD を 0-99 に。各桁が 9 を超えていれば None
+pub const fn from_bcd(v: u8) -> Option<u8> {
+    let (hi, lo) = (v >> 4, v & 0x0f);
+    if hi > 9 || lo > 9 { None } else { Some(hi * 10 + lo) }
+}
+
+/// 年・月・日と曜日を詰めた SHA-1 の W[8]
+pub const fn date8(year: u8, month: u8, day: u8) -> u32 {
+    ((to_bcd(year) as u32) << 24)
+        | ((to_bcd(month) as u32) << 16)
+        | ((to_bcd(day) as u32) << 8)
+        | weekday(year, month, day) as u32
+}
+
+/// 時・分・秒を詰めた SHA-1 の W[9]。午後は時に 0x40 が立つ
+pub const fn time9(hour: u8, minute: u8, second: u8) -> u32 {
+    let pm = if hour >= 12 { 0x40 } else { 0 };
+    (((to_bcd(hour) | pm) as u32) << 24) | ((to_bcd(minute) as u32) << 16) | ((to_bcd(second) as u32) << 8)
+}
+
+/**
+DS の時計が表せる日時
+
+フィールドは生成時に検査するので、存在しない日時は作れない
+並びの順序は時刻の順と一致する
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DsDateTime {
+    year: u8,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DsDateTime {
+    /// 2000-01-01 00:00:00
+    pub const MIN: DsDateTime = DsDateTime { year: 0, month: 1, day: 1, hour: 0, minute: 0, second: 0 };
+    /// 2099-12-31 23:59:59
+    pub const MAX: DsDateTime = DsDateTime { year: 99, month: 12, day: 31, hour: 23, minute: 59, second: 59 };
+
+    /// 年は下2桁 (00-99)
+    pub fn try_new(year: u8, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Result<Self, ConfigError> {
+        if year >= 100 || day == 0 || day > days_in_month(year, month) {
+            return Err(ConfigError::InvalidDate { year, month, day });
+        }
+        if hour >= 24 || minute >= 60 || second >= 60 {
+            return Err(ConfigError::InvalidTime { hour, minute, second });
+        }
+        Ok(Self { year, month, day, hour, minute, second })
+    }
+
+    pub fn year(&self) -> u8 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    pub fn date(&self) -> GameDate {
+        GameDate::new(self.year, self.month, self.day)
+    }
+
+    pub fn weekday(&self) -> u8 {
+        weekday(self.year, self.month, self.day)
+    }
+
+    pub fn date8(&self) -> u32 {
+        date8(self.year, self.month, self.day)
+    }
+
+    pub fn time9(&self) -> u32 {
+        time9(self.hour, self.minute, self.second)
+    }
+
+    /// date8 / time9 から戻す。曜日や午後のフラグが日時と食い違っていればエラー
+    pub fn from_date8_time9(date8: u32, time9: u32) -> Result<Self, ConfigError> {
+        let [y, mo, d, _] = date8.to_be_bytes();
+        let [h, mi, s, _] = time9.to_be_bytes();
+        let digits = |v: u8, raw: u32| from_bcd(v).ok_or(ConfigError::InvalidBcd(raw));
+        let dt = Self::try_new(
+            digits(y, date8)?,
+            digits(mo, date8)?,
+            digits(d, date8)?,
+            digits(h & !0x40, time9)?,
+            digits(mi, time9)?,
+            digits(s, time9)?,
+        )?;
+        if dt.date8() != date8 {
+            return Err(ConfigError::InvalidBcd(date8));
+        }
+        if dt.time9() != time9 {
+            return Err(ConfigError::InvalidBcd(time9));
+        }
+        Ok(dt)
+    }
+
+    /// 2000-01-01 00:00:00 からの秒数
+    fn to_seconds(self) -> i64 {
+        days_since_2000(self.year, self.month, self.day) as i64 * SECONDS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    fn from_seconds(total: i64) -> Option<Self> {
+        if !(0..SECONDS_IN_RANGE).contains(&total) {
+            return None;
+        }
+        let mut days = (total / SECONDS_PER_DAY) as u32;
+        let secs = (total % SECONDS_PER_DAY) as u32;
+
+        let mut year = 0u8;
+        loop {
+            let len = if is_leap_year(year) { 366 } else { 365 };
+            if days < len {
+                break;
+            }
+            days -= len;
+            year += 1;
+        }
+        let mut month = 1u8;
+        while days >= days_in_month(year, month) as u32 {
+            days -= days_in_month(year, month) as u32;
+            month += 1;
+        }
+        Some(Self {
+            year,
+            month,
+            day: days as u8 + 1,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+        })
+    }
+
+    /// 2000-2099 年の範囲を出たら None
+    pub fn checked_add_seconds(self, seconds: i64) -> Option<Self> {
+        Self::from_seconds(self.to_seconds().checked_add(seconds)?)
+    }
+
+    pub fn checked_sub_seconds(self, seconds: i64) -> Option<Self> {
+        Self::from_seconds(self.to_seconds().checked_sub(seconds)?)
+    }
+}
+
+impl TryFrom<GameTime> for DsDateTime {
+    type Error = ConfigError;
+
+    fn try_from(t: GameTime) -> Result<Self, Self::Error> {
+        Self::try_new(t.year(), t.month(), t.day(), t.hour(), t.minute(), t.second())
+    }
+}
+
+impl TryFrom<NaiveDateTime> for DsDateTime {
+    type Error = ConfigError;
+
+    fn try_from(dt: NaiveDateTime) -> Result<Self, Self::Error> {
+        let year = dt.year();
+        if !(2000..=2099).contains(&year) {
+            return Err(ConfigError::YearOutOfRange(year));
+        }
+        Self::try_new(
+            (year - 2000) as u8,
+            dt.month() as u8,
+            dt.day() as u8,
+            dt.hour() as u8,
+            dt.minute() as u8,
+            dt.second() as u8,
+        )
+    }
+}
+
+impl From<DsDateTime> for NaiveDateTime {
+    fn from(dt: DsDateTime) -> Self {
+        NaiveDate::from_ymd_opt(2000 + dt.year as i32, dt.month as u32, dt.day as u32)
+            .and_then(|d| d.and_hms_opt(dt.hour as u32, dt.minute as u32, dt.second as u32))
+            .expect("DsDateTime is always a valid date")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_dates() -> impl Iterator<Item = (u8, u8, u8)> {
+        (0..100u8).flat_map(|y| (1..=12u8).flat_map(move |m| (1..=days_in_month(y, m)).map(move |d| (y, m, d))))
+    }
+
+    #[test]
+    fn weekday_matches_chrono_for_every_day() {
+        let mut count = 0;
+        for (y, m, d) in all_dates() {
+            let expected = NaiveDate::from_ymd_opt(2000 + y as i32, m as u32, d as u32)
+                .unwrap()
+                .weekday()
+                .num_days_from_sunday();
+            assert_eq!(weekday(y, m, d) as u32, expected, "{y:02}-{m:02}-{d:02}");
+            count += 1;
+        }
+        assert_eq!(count, 36525);
+    }
+
+    #[test]
+    fn every_fourth_year_is_leap_including_2000() {
+        assert_eq!(days_in_month(0, 2), 29);
+        assert_eq!(days_in_month(1, 2), 28);
+        assert_eq!(days_in_month(96, 2), 29);
+        assert_eq!(days_in_month(26, 13), 0);
+        assert!(DsDateTime::try_new(0, 2, 29, 0, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_fields() {
+        assert!(DsDateTime::try_new(26, 13, 1, 0, 0, 0).is_err());
+        assert!(DsDateTime::try_new(26, 1, 32, 0, 0, 0).is_err());
+        assert!(DsDateTime::try_new(26, 2, 29, 0, 0, 0).is_err());
+        assert!(DsDateTime::try_new(100, 1, 1, 0, 0, 0).is_err());
+        assert_eq!(
+            DsDateTime::try_new(26, 1, 1, 24, 0, 0),
+            Err(ConfigError::InvalidTime { hour: 24, minute: 0, second: 0 })
+        );
+        assert!(GameTime::try_new(26, 1, 1, 0, 60, 0).is_err());
+    }
+
+    #[test]
+    fn bcd_roundtrip() {
+        let dt = DsDateTime::try_new(26, 1, 24, 23, 59, 59).unwrap();
+        assert_eq!(dt.date8(), 0x26012406);
+        assert_eq!(dt.time9(), 0x63595900);
+        for (y, m, d) in all_dates() {
+            let dt = DsDateTime::try_new(y, m, d, d % 24, (m * 4) % 60, y % 60).unwrap();
+            assert_eq!(DsDateTime::from_date8_time9(dt.date8(), dt.time9()), Ok(dt));
+        }
+        // 曜日の食い違い・BCD でない桁・午後フラグのない 13 時
+        assert!(DsDateTime::from_date8_time9(0x26012405, 0).is_err());
+        assert!(DsDateTime::from_date8_time9(0x260A0100, 0).is_err());
+        assert!(DsDateTime::from_date8_time9(0x26012406, 0x13000000).is_err());
+    }
+
+    #[test]
+    fn checked_add_crosses_boundaries_and_stops_at_range_end() {
+        let dt = DsDateTime::try_new(0, 2, 28, 23, 59, 59).unwrap();
+        assert_eq!(dt.checked_add_seconds(1), DsDateTime::try_new(0, 2, 29, 0, 0, 0).ok());
+        assert_eq!(
+            DsDateTime::try_new(25, 12, 31, 23, 59, 30).unwrap().checked_add_seconds(45),
+            DsDateTime::try_new(26, 1, 1, 0, 0, 15).ok()
+        );
+        assert_eq!(DsDateTime::MAX.checked_add_seconds(1), None);
+        assert_eq!(DsDateTime::MIN.checked_sub_seconds(1), None);
+        assert_eq!(DsDateTime::MAX.checked_sub_seconds(SECONDS_IN_RANGE - 1), Some(DsDateTime::MIN));
+
+        let step = 7 * SECONDS_PER_DAY + 3 * 3600 + 17;
+        let mut cur = DsDateTime::MIN;
+        while let Some(next) = cur.checked_add_seconds(step) {
+            assert_eq!(next.checked_sub_seconds(step), Some(cur));
+            let expected = NaiveDateTime::from(cur) + chrono::Duration::seconds(step);
+            assert_eq!(NaiveDateTime::from(next), expected);
+            cur = next;
+        }
+    }
+
+    #[test]
+    fn chrono_roundtrip() {
+        let dt = DsDateTime::try_new(99, 12, 31, 12, 0, 1).unwrap();
+        let naive = NaiveDateTime::from(dt);
+        assert_eq!(naive.to_string(), "2099-12-31 12:00:01");
+        assert_eq!(DsDateTime::try_from(naive), Ok(dt));
+
+        let too_late = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(DsDateTime::try_from(too_late), Err(ConfigError::YearOutOfRange(2100)));
+    }
+}
@@ -1,106 +1,83 @@
+use crate::error::ConfigError;
+use crate::models::ds_date_time;
+
+/// 2000-2099 年の日付。フィールドは生成時に検査するので、存在しない日付は作れない
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
 pub struct GameDate {
-    pub year: u8,
-    pub month: u8,
-    pub day: u8,
+    year: u8,
+    month: u8,
+    day: u8,
 }
 
 impl GameDate {
+    /// 存在しない日付なら panic する。外から受け取った値は `try_new` で検査する
     pub fn new(year: u8, month: u8, day: u8) -> Self{
-        Self { year, month, day }
+        Self::try_new(year, month, day).unwrap_or_else(|e| panic!("{e}"))
     }
 
-    pub fn weekday(&self) -> u8 {
-        // Zeller's Congruence algorithm to calculate the day of the week
-        let mut m = self.month as i32;
-        let mut y = self.year as i32;
-
-        if m < 3 {
-            m += 12;
-            y = if y == 0 { 94 } else { y - 1 }; // Adjust for year wrap-around
+    /// 年 00-99 と、その月に存在する日だけを受け付ける
+    pub fn try_new(year: u8, month: u8, day: u8) -> Result<Self, ConfigError> {
+        let date = Self { year, month, day };
+        if year >= 100 || day == 0 || day > date.days_in_month() {
+            return Err(ConfigError::InvalidDate { year, month, day });
         }
+        Ok(date)
+    }
 
-        let weekday = (self.day as i32 + ((13 * m + 8) / 5) + y + (y >> 2)) % 7;
+    pub fn year(&self) -> u8 {
+        self.year
+    }
 
-        weekday as u8 // 0 = Sunday, 1 = Monday, ..., 6 = Saturday
+    pub fn month(&self) -> u8 {
+        self.month
     }
 
-    pub fn days_in_month(&self) -> u8 {
-        match self.month {
-            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
-            4 | 6 | 9 | 11 => 30,
-            2 => {
-                if self.year % 4 == 0 && self.year % 100 != 0 {
-                    29
-                } else {
-                    28
-                }
-            }
-            _ => 0, // Invalid month
-        }
+    pub fn day(&self) -> u8 {
+        self.day
     }
 
-    pub fn add_day(&mut self) {
-        self.day += 1;
-        if self.day > self.days_in_month() {
-            self.day = 1;
-            self.add_month();
-        }
+    /// 0 = 日曜日, 1 = 月曜日, ..., 6 = 土曜日
+    pub fn weekday(&self) -> u8 {
+        ds_date_time::weekday(self.year, self.month, self.day)
     }
 
-    pub fn add_month(&mut self) {
-        self.month += 1;
-        if self.month > 12 {
-            self.month = 1;
-            self.year += 1;
-        }
+    pub fn days_in_month(&self) -> u8 {
+        ds_date_time::days_in_month(self.year, self.month)
     }
 
-    pub fn get_date8_format(&self) -> u32 {
-        // 10進数を16進数に変換する処理を直接ビット演算で行う
-        let hex_year = ((self.year / 10) << 4) | (self.year % 10);
-        let hex_month = ((self.month / 10) << 4) | (self.month % 10);
-        let hex_day = ((self.day / 10) << 4) | (self.day % 10);
+    /// 翌日。2099-12-31 の翌日は DS の時計にないので None
+    pub fn next_day(&self) -> Option<Self> {
+        let (year, month, day) = if self.day < self.days_in_month() {
+            (self.year, self.month, self.day + 1)
+        } else if self.month < 12 {
+            (self.year, self.month + 1, 1)
+        } else {
+            (self.year + 1, 1, 1)
+        };
+        Self::try_new(year, month, day).ok()
+    }
 
-        ((hex_year as u32) << 24) | ((hex_month as u32) << 16) | ((hex_day as u32) << 8) | self.weekday() as u32
+    pub fn get_date8_format(&self) -> u32 {
+        ds_date_time::date8(self.year, self.month, self.day)
     }
 }
 
-pub fn build_autumn_and_winter() -> Vec<GameDate> {
-    let mut dates = Vec::new();
-    for year in 0..=99u8 {
-        for month in 1..=12u8 {
-            if month % 4 == 2 || month % 4 == 1 {
-                continue;
-            }
-            let days = GameDate::new(year, month, 1).days_in_month();
-            if days == 0 {
-                continue;
-            }
-            for day in 1..=days {
-                dates.push(GameDate { year, month, day });
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_day_rolls_over_and_stops_after_2099() {
+        assert_eq!(GameDate::new(0, 2, 28).next_day(), Some(GameDate::new(0, 2, 29)));
+        assert_eq!(GameDate::new(1, 2, 28).next_day(), Some(GameDate::new(1, 3, 1)));
+        assert_eq!(GameDate::new(33, 12, 31).next_day(), Some(GameDate::new(34, 1, 1)));
+        assert_eq!(GameDate::new(99, 12, 31).next_day(), None);
     }
-    dates
-}
 
-pub fn build_date_except_summer() -> Vec<GameDate> {
-    let mut dates = Vec::new();
-    for year in 0..=99u8 {
-        for month in 1..=12u8 {
-            if month % 4 == 2 {
-                continue;
-            }
-            let days = GameDate::new(year, month, 1).days_in_month();
-            if days == 0 {
-                continue;
-            }
-            for day in 1..=days {
-                dates.push(GameDate { year, month, day });
-            }
-        }
+    #[test]
+    #[should_panic]
+    fn new_rejects_missing_dates() {
+        GameDate::new(33, 2, 30);
     }
-    dates
 }
@@ -1,5 +1,7 @@
+use super::ds_date_time::days_in_month;
 use super::game_date::GameDate;
 use super::field_range::FieldRange;
+use crate::error::ConfigError;
 
 #[derive(Debug, Clone, Copy)]
 pub struct GameDateSpec {
@@ -12,18 +14,16 @@ pub struct GameDateSpec {
  * 検索範囲の最小を定める
  */
 impl GameDateSpec {
-    pub fn start(&self) -> GameDate {
-        GameDate {
-            year: self.year.min,
-            month: self.month.min,
-            day: self.day.min,
-        }
+    /// 各フィールドの最小の組み合わせ。その日付が存在しなければエラー
+    pub fn start(&self) -> Result<GameDate, ConfigError> {
+        GameDate::try_new(self.year.min, self.month.min, self.day.min)
     }
 }
 
 // イテレータ実装
 pub struct GameDateIterator {
-    current: GameDate,
+    /// 次に返す (年, 月, 日)。進める途中では存在しない日付 (2/30 など) も通る
+    current: (u8, u8, u8),
     spec: GameDateSpec,
     finished: bool,
 }
@@ -36,45 +36,98 @@ impl Iterator for GameDateIterator {
             return None;
         }
 
-        let out = self.current;
+        let (year, month, day) = self.current;
         self.advance();
-        Some(out)
+        Some(GameDate::new(year, month, day))
     }
 }
 
 impl GameDateIterator {
     pub fn new(spec: GameDateSpec) -> Self {
-        Self {
-            current: spec.start(),
+        let mut it = Self {
+            current: (spec.year.min, spec.month.min, spec.day.min),
             spec,
-            finished: false,
+            finished: spec.year.min > spec.year.max
+                || spec.month.min > spec.month.max.min(12)
+                || spec.day.min > spec.day.max,
+        };
+        if !it.finished && !it.is_valid() {
+            it.advance();
         }
+        it
+    }
+
+    /// 範囲の中でも、その月に存在しない日 (2/30 など) と 2099 年より後は飛ばす
+    fn is_valid(&self) -> bool {
+        let (year, month, day) = self.current;
+        year < 100 && day >= 1 && day <= days_in_month(year, month)
     }
 
     #[inline]
     fn advance(&mut self) {
+        loop {
+            if !self.step() {
+                // 完全終了
+                self.finished = true;
+                return;
+            }
+            if self.is_valid() {
+                return;
+            }
+        }
+    }
+
+    /// 年・月・日の箱の中を1つ進める。箱を出たら false
+    fn step(&mut self) -> bool {
+        let (year, month, day) = &mut self.current;
         // 日
-        self.current.day += 1;
-        let dim = self.current.days_in_month();
-        if self.current.day <= dim && self.spec.day.contains(self.current.day) {
-            return;
+        if *day < self.spec.day.max {
+            *day += 1;
+            return true;
+        }
+        *day = self.spec.day.min;
+
+        // 月 (13 以上にはしない)
+        if *month < self.spec.month.max.min(12) {
+            *month += 1;
+            return true;
         }
-        self.current.day = self.spec.day.min;
+        *month = self.spec.month.min;
 
-        // 月
-        self.current.month += 1;
-        if self.current.month <= self.spec.month.max {
-            return;
+        // 年 (2099 年より後にはしない)
+        if *year < self.spec.year.max.min(99) {
+            *year += 1;
+            return true;
         }
-        self.current.month = self.spec.month.min;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // 年
-        self.current.year += 1;
-        if self.current.year <= self.spec.year.max {
-            return;
+    fn spec(year: [u8; 2], month: [u8; 2], day: [u8; 2]) -> GameDateSpec {
+        GameDateSpec {
+            year: FieldRange { min: year[0], max: year[1] },
+            month: FieldRange { min: month[0], max: month[1] },
+            day: FieldRange { min: day[0], max: day[1] },
         }
+    }
+
+    #[test]
+    fn skips_days_missing_from_the_month() {
+        let dates: Vec<(u8, u8)> = GameDateIterator::new(spec([1, 1], [1, 3], [29, 31]))
+            .map(|d| (d.month(), d.day()))
+            .collect();
+        assert_eq!(dates, [(1, 29), (1, 30), (1, 31), (3, 29), (3, 30), (3, 31)]);
+    }
 
-        // 完全終了
-        self.finished = true;
+    #[test]
+    fn narrow_day_range_never_leaves_the_month_range() {
+        let dates: Vec<GameDate> = GameDateIterator::new(spec([10, 11], [11, 13], [31, 31])).collect();
+        assert!(dates.iter().all(|d| d.month() <= 12 && d.day() <= d.days_in_month()));
+        assert_eq!(dates.len(), 2);
+        assert_eq!(GameDateIterator::new(spec([10, 10], [2, 2], [30, 31])).count(), 0);
     }
 }
@@ -1,5 +1,9 @@
+use super::game_date::GameDate;
+use super::game_date_iterator::{GameDateIterator, GameDateSpec};
 use super::game_time::*;
 use super::field_range::*;
+use super::time_set::{TimeOfDay, TimeSet};
+use crate::error::ConfigError;
 
 #[derive(Debug, Clone, Copy)]
 pub struct GameTimeSpec {
@@ -15,96 +19,76 @@ pub struct GameTimeSpec {
  * 検索範囲の最小を定める
  */
 impl GameTimeSpec {
-    pub fn start(&self) -> GameTime {
-        GameTime {
-            year: self.year.min,
-            month: self.month.min,
-            day: self.day.min,
-            hour: self.hour.min,
-            minute: self.minute.min,
-            second: self.second.min,
+    /// 各フィールドの最小の組み合わせ。その日時が存在しなければエラー
+    pub fn start(&self) -> Result<GameTime, ConfigError> {
+        GameTime::try_new(self.year.min, self.month.min, self.day.min, self.hour.min, self.minute.min, self.second.min)
+    }
+
+    pub fn date_spec(&self) -> GameDateSpec {
+        GameDateSpec {
+            year: self.year,
+            month: self.month,
+            day: self.day,
         }
     }
+
+    pub fn time_set(&self) -> TimeSet {
+        let range = |r: FieldRange<u8>| [r.min as u32, r.max as u32];
+        TimeSet::from_ranges(range(self.hour), range(self.minute), range(self.second))
+    }
 }
 
 
-// イテレータ実装
+/**
+日付ごとに `TimeSet` の時刻を順に返すイテレータ
+
+0 時をまたぐ時刻は翌日の日付になる。翌日が 2099 年を越える分は返さない
+*/
 pub struct GameTimeIterator {
-    current: GameTime,
-    spec: GameTimeSpec,
-    finished: bool,
+    dates: GameDateIterator,
+    current: Option<GameDate>,
+    times: Vec<TimeOfDay>,
+    index: usize,
 }
 
 impl Iterator for GameTimeIterator {
     type Item = GameTime;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
+        loop {
+            let date = self.current?;
+            let Some(&time) = self.times.get(self.index) else {
+                self.current = self.dates.next();
+                self.index = 0;
+                continue;
+            };
+            self.index += 1;
+
+            let date = (0..time.day_offset).try_fold(date, |d, _| d.next_day());
+            let Some(date) = date else {
+                continue;
+            };
+            return Some(GameTime::new(date.year(), date.month(), date.day(), time.hour, time.minute, time.second));
         }
-
-        let out = self.current;
-        self.advance();
-        Some(out)
     }
 }
 
 
 impl GameTimeIterator {
     pub fn new(spec: GameTimeSpec) -> Self {
-        Self {
-            current: spec.start(),
-            spec,
-            finished: false,
-        }
+        Self::with_times(spec.date_spec(), &spec.time_set())
     }
 
-    #[inline]
-    fn advance(&mut self) {
-        // 秒
-        self.current.second += 1;
-        if self.current.second <= self.spec.second.max {
-            return;
-        }
-        self.current.second = self.spec.second.min;
-
-        // 分
-        self.current.minute += 1;
-        if self.current.minute <= self.spec.minute.max {
-            return;
-        }
-        self.current.minute = self.spec.minute.min;
-
-        // 時
-        self.current.hour += 1;
-        if self.current.hour <= self.spec.hour.max {
-            return;
-        }
-        self.current.hour = self.spec.hour.min;
-
-        // 日
-        self.current.day += 1;
-        let dim = self.current.days_in_month();
-        if self.current.day <= dim && self.spec.day.contains(self.current.day) {
-            return;
-        }
-        self.current.day = self.spec.day.min;
-
-        // 月
-        self.current.month += 1;
-        if self.current.month <= self.spec.month.max {
-            return;
-        }
-        self.current.month = self.spec.month.min;
-
-        // 年
-        self.current.year += 1;
-        if self.current.year <= self.spec.year.max {
-            return;
+    pub fn with_times(dates: GameDateSpec, times: &TimeSet) -> Self {
+        let mut dates = GameDateIterator::new(dates);
+        let times = times.times();
+        let current = if times.is_empty() { None } else { dates.next() };
+        Self {
+            dates,
+            current,
+            times,
+            index: 0,
         }
-
-        // 完全終了
-        self.finished = true;
     }
 }
 
@@ -129,12 +113,12 @@ mod tests{
         let mut it = GameTimeIterator::new(spec);
         let v = it.next().unwrap();
 
-        assert_eq!(v.year, 26);
-        assert_eq!(v.month, 1);
-        assert_eq!(v.day, 1);
-        assert_eq!(v.hour, 0);
-        assert_eq!(v.minute, 0);
-        assert_eq!(v.second, 0);
+        assert_eq!(v.year(), 26);
+        assert_eq!(v.month(), 1);
+        assert_eq!(v.day(), 1);
+        assert_eq!(v.hour(), 0);
+        assert_eq!(v.minute(), 0);
+        assert_eq!(v.second(), 0);
 
         assert!(it.next().is_none());
     }
@@ -158,10 +142,10 @@ mod tests{
         let c = it.next().unwrap();
         let d = it.next().unwrap();
 
-        assert_eq!((a.minute, a.second), (0, 58));
-        assert_eq!((b.minute, b.second), (0, 59));
-        assert_eq!((c.minute, c.second), (1, 58));
-        assert_eq!((d.minute, d.second), (1, 59));
+        assert_eq!((a.minute(), a.second()), (0, 58));
+        assert_eq!((b.minute(), b.second()), (0, 59));
+        assert_eq!((c.minute(), c.second()), (1, 58));
+        assert_eq!((d.minute(), d.second()), (1, 59));
 
         assert!(it.next().is_none());
     }
@@ -180,11 +164,35 @@ mod tests{
         let mut it = GameTimeIterator::new(spec);
 
         let jan = it.next().unwrap();
-        let feb = it.next().unwrap();
-
-        assert_eq!((jan.month, jan.day), (1, 31));
-        assert_eq!((feb.month, feb.day), (2, 31)); // ← ここで止まる設計なら OK
 
+        // 2/31 は存在しないので飛ばす
+        assert_eq!((jan.month(), jan.day()), (1, 31));
         assert!(it.next().is_none());
     }
+
+    #[test]
+    fn iter_window_rolls_over_to_next_date() {
+        let dates = GameDateSpec {
+            year: FieldRange { min: 26, max: 26 },
+            month: FieldRange { min: 2, max: 2 },
+            day: FieldRange { min: 28, max: 28 },
+        };
+        let times = TimeSet::window((23, 59, 59), (0, 0, 1)).unwrap();
+        let v: Vec<GameTime> = GameTimeIterator::with_times(dates, &times).collect();
+        assert_eq!(
+            v,
+            [
+                GameTime::new(26, 2, 28, 23, 59, 59),
+                GameTime::new(26, 3, 1, 0, 0, 0),
+                GameTime::new(26, 3, 1, 0, 0, 1),
+            ]
+        );
+
+        let last = GameDateSpec {
+            year: FieldRange { min: 99, max: 99 },
+            month: FieldRange { min: 12, max: 12 },
+            day: FieldRange { min: 31, max: 31 },
+        };
+        assert_eq!(GameTimeIterator::with_times(last, &times).count(), 1);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 // Gameversion
+use std::ops::RangeInclusive;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -9,6 +11,21 @@ pub enum GameVersion {
     White2,
 }
 
+impl GameVersion {
+    /**
+    プロファイルを確かめるときの Timer0 の目安
+
+    同梱の ds_config.json の実機プロファイル (BW 0x0C68・0x0C7A、BW2 0x10F7・0x10FA) を含むように広めにとったもので、
+    出典のある範囲ではない。本体ごとの違いは分からないのでバージョンだけで決める。外れていても警告にとどめる
+    */
+    pub const fn typical_timer0(self) -> RangeInclusive<u16> {
+        match self {
+            GameVersion::Black | GameVersion::White => 0x0C60..=0x0C90,
+            GameVersion::Black2 | GameVersion::White2 => 0x10E0..=0x1120,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NazoValues {
     pub nazo1: u32,
@@ -77,4 +94,18 @@ impl VersionConfig {
             },
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typical_ranges_cover_bundled_profiles() {
+        assert!(GameVersion::White2.typical_timer0().contains(&0x10FA));
+        assert!(GameVersion::White2.typical_timer0().contains(&0x10F7));
+        assert!(GameVersion::Black.typical_timer0().contains(&0x0C7A));
+        assert!(GameVersion::White.typical_timer0().contains(&0x0C68));
+        assert!(!GameVersion::Black.typical_timer0().contains(&0x10FA));
+    }
+}
@@ -0,0 +1,103 @@
+/*!
+本体の種類
+
+seed0 の data[7] に入る GxStat ^ Frame は本体で決まる
+以前の `is_dslite` で区別していた DS と DS Lite だけを扱う。ほかの本体・エミュレータは値の裏付けがとれたら足す
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConfigError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hardware {
+    /// 初代 DS
+    #[serde(rename = "ds")]
+    DsPhat,
+    #[serde(rename = "ds_lite")]
+    DsLite,
+}
+
+const NAMES: [(Hardware, &str); 2] = [(Hardware::DsPhat, "ds"), (Hardware::DsLite, "ds_lite")];
+
+impl Hardware {
+    pub const ALL: [Hardware; 2] = [Hardware::DsPhat, Hardware::DsLite];
+
+    /// 以前の `is_dslite` の値から
+    pub const fn from_is_dslite(is_dslite: bool) -> Self {
+        if is_dslite { Hardware::DsLite } else { Hardware::DsPhat }
+    }
+
+    /// GxStat (DS と DS Lite で同じ)
+    pub const fn gx_stat(self) -> u32 {
+        0x0600_0000
+    }
+
+    /// DS Lite は 6、DS は 8
+    pub const fn frame(self) -> u32 {
+        match self {
+            Hardware::DsPhat => 8,
+            Hardware::DsLite => 6,
+        }
+    }
+
+    /// data[7] に使う値 (MAC と混ぜる前、ビッグエンディアン)
+    pub const fn gxframe_xor_frame(self) -> u32 {
+        self.gx_stat() ^ self.frame()
+    }
+
+    pub fn name(self) -> &'static str {
+        NAMES.iter().find(|(h, _)| *h == self).map_or("", |(_, n)| n)
+    }
+}
+
+impl fmt::Display for Hardware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// `ds` `ds_lite` (大文字小文字と `-` / `_` は区別しない)
+impl FromStr for Hardware {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_ascii_lowercase().replace('-', "_");
+        let key = match key.as_str() {
+            "dslite" | "lite" => "ds_lite",
+            "ds_phat" | "phat" => "ds",
+            k => k,
+        };
+        NAMES
+            .iter()
+            .find(|(_, n)| *n == key)
+            .map(|(h, _)| *h)
+            .ok_or_else(|| ConfigError::UnknownHardware(s.trim().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_matches_the_old_dslite_flag() {
+        assert_eq!(Hardware::from_is_dslite(true).gxframe_xor_frame(), 0x0600_0006);
+        assert_eq!(Hardware::from_is_dslite(false).gxframe_xor_frame(), 0x0600_0008);
+    }
+
+    #[test]
+    fn names_roundtrip() {
+        for h in Hardware::ALL {
+            assert_eq!(h.name().parse::<Hardware>(), Ok(h));
+            assert_eq!(serde_json::to_string(&h).unwrap(), format!("\"{}\"", h.name()));
+        }
+        assert_eq!("DS-Lite".parse::<Hardware>(), Ok(Hardware::DsLite));
+        for unsupported in ["dsi", "3ds", "melonDS", "switch"] {
+            assert!(unsupported.parse::<Hardware>().is_err(), "{unsupported}");
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::ConfigError;
+use crate::models::KeyPresses;
+use crate::models::key_presses::KEY_BITS_MASK;
+
+/**
+検索するキー入力の絞り込み
+
+既定ではゲームが受け付ける 2160 通りすべてを通す
+実機で同時に押せるキーの数や、壊れて押せないボタンに合わせて候補を減らすのに使う
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyPressFilter {
+    /// 同時に押せるキーの数の上限
+    max_keys: Option<u32>,
+    /// 押してはいけないキー (押されたビットが1のマスク)
+    forbidden: u16,
+    /// 指定があればこの組み合わせだけを通す
+    combos: Option<Vec<KeyPresses>>,
+}
+
+impl KeyPressFilter {
+    /// 何も絞り込まない
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn max_keys(mut self, n: u32) -> Self {
+        self.max_keys = Some(n);
+        self
+    }
+
+    /// `keys` で押されているキーを含む組み合わせを除く
+    pub fn forbid(mut self, keys: KeyPresses) -> Self {
+        self.forbidden |= keys.pressed_mask();
+        self
+    }
+
+    /// `keys` で押されているキーだけを使う組み合わせに限る
+    pub fn allow_only(mut self, keys: KeyPresses) -> Self {
+        self.forbidden |= !keys.pressed_mask() & KEY_BITS_MASK;
+        self
+    }
+
+    /// 指定した組み合わせだけを通す (ほかの条件とも両立する必要がある)
+    pub fn only_combos(mut self, combos: impl IntoIterator<Item = KeyPresses>) -> Self {
+        self.combos = Some(combos.into_iter().collect());
+        self
+    }
+
+    /// 何も絞り込まない設定か
+    pub fn is_all(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, key_presses: KeyPresses) -> bool {
+        if !KeyPresses::is_valid_raw(key_presses.raw()) {
+            return false;
+        }
+        if self.max_keys.is_some_and(|max| key_presses.pressed_count() > max) {
+            return false;
+        }
+        if key_presses.pressed_mask() & self.forbidden != 0 {
+            return false;
+        }
+        match &self.combos {
+            Some(combos) => combos.contains(&key_presses),
+            None => true,
+        }
+    }
+
+    /// 条件を満たすキー入力を生の値の昇順で返す
+    pub fn iter(&self) -> impl Iterator<Item = KeyPresses> + '_ {
+        KeyPresses::iter_valid().filter(|&k| self.matches(k))
+    }
+
+    /// GPU に渡すキー入力の一覧 (生の値)
+    pub fn keypress_list(&self) -> Vec<u32> {
+        self.iter().map(|k| k.raw() as u32).collect()
+    }
+
+    /// "A+START, none, B" のようにカンマで区切った組み合わせの一覧を読む
+    pub fn parse_combos(s: &str) -> Result<Vec<KeyPresses>, ConfigError> {
+        s.split(',').map(str::parse).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(s: &str) -> KeyPresses {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn default_passes_every_valid_keypress() {
+        assert!(KeyPressFilter::all().is_all());
+        assert_eq!(KeyPressFilter::all().iter().count(), 2160);
+    }
+
+    #[test]
+    fn max_keys_limits_simultaneous_presses() {
+        let none_or_one = KeyPressFilter::all().max_keys(1);
+        assert_eq!(none_or_one.iter().count(), 13);
+        assert!(none_or_one.matches(combo("none")));
+        assert!(none_or_one.matches(combo("START")));
+        assert!(!none_or_one.matches(combo("A+B")));
+    }
+
+    #[test]
+    fn forbid_and_allow_only() {
+        let no_lr = KeyPressFilter::all().forbid(combo("L+R"));
+        assert!(no_lr.iter().all(|k| !k.is_pressed(8) && !k.is_pressed(9)));
+        assert!(no_lr.matches(combo("A+START")));
+        assert!(!no_lr.matches(combo("A+L")));
+
+        let face = KeyPressFilter::all().allow_only(combo("A+B+X+Y"));
+        assert_eq!(face.iter().count(), 16);
+        assert!(!face.matches(combo("A+UP")));
+    }
+
+    #[test]
+    fn combos_are_a_whitelist() {
+        let combos = KeyPressFilter::parse_combos("none, A+START , b").unwrap();
+        let filter = KeyPressFilter::all().only_combos(combos.clone());
+        let found: Vec<KeyPresses> = filter.iter().collect();
+        assert_eq!(found.len(), 3);
+        assert!(combos.iter().all(|c| found.contains(c)));
+        assert_eq!(filter.keypress_list().len(), 3);
+
+        // 上下同時押しはゲームが受け付けないので、指定しても通らない
+        let invalid = KeyPressFilter::all().only_combos([combo("UP+DOWN")]);
+        assert_eq!(invalid.iter().count(), 0);
+
+        assert!(KeyPressFilter::parse_combos("A,FOO").is_err());
+    }
+}
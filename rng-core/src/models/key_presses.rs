@@ -1,3 +1,12 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConfigError;
+
 // キーのビット位置
 const KEY_A_BIT: u32 = 0;
 const KEY_B_BIT: u32 = 1;
@@ -20,6 +29,97 @@ const fn key_mask(bit: u32) -> u16 {
 // 定数定義
 const KEY_RANGE_START: u16 = 0x2000;
 const KEY_RANGE_END: u16 = 0x2fff;
+/// 12 キー分のビット
+pub(crate) const KEY_BITS_MASK: u16 = 0x0fff;
+
+/// DS のボタンひとつ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    A,
+    B,
+    X,
+    Y,
+    Up,
+    Down,
+    Left,
+    Right,
+    L,
+    R,
+    Start,
+    Select,
+}
+
+impl Key {
+    /// 表示の順
+    pub const ALL: [Key; 12] = [
+        Key::A,
+        Key::B,
+        Key::X,
+        Key::Y,
+        Key::Up,
+        Key::Down,
+        Key::Left,
+        Key::Right,
+        Key::L,
+        Key::R,
+        Key::Start,
+        Key::Select,
+    ];
+
+    /// キー入力値でのビット位置
+    pub const fn bit(self) -> u32 {
+        match self {
+            Key::A => KEY_A_BIT,
+            Key::B => KEY_B_BIT,
+            Key::X => KEY_X_BIT,
+            Key::Y => KEY_Y_BIT,
+            Key::Up => KEY_UP_BIT,
+            Key::Down => KEY_DOWN_BIT,
+            Key::Left => KEY_LEFT_BIT,
+            Key::Right => KEY_RIGHT_BIT,
+            Key::L => KEY_L_BIT,
+            Key::R => KEY_R_BIT,
+            Key::Start => KEY_START_BIT,
+            Key::Select => KEY_SELECT_BIT,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Key::A => "A",
+            Key::B => "B",
+            Key::X => "X",
+            Key::Y => "Y",
+            Key::Up => "UP",
+            Key::Down => "DOWN",
+            Key::Left => "LEFT",
+            Key::Right => "RIGHT",
+            Key::L => "L",
+            Key::R => "R",
+            Key::Start => "START",
+            Key::Select => "SELECT",
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 大文字小文字は問わない
+impl FromStr for Key {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Key::ALL
+            .into_iter()
+            .find(|k| k.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| ConfigError::InvalidKeys(name.to_string()))
+    }
+}
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +145,36 @@ impl KeyPresses {
         (self.keys & key_mask(bit)) == 0
     }
 
+    /// 押すキーを並べて生成する (何も押さないなら空)
+    pub fn from_keys(keys: &[Key]) -> Self {
+        let mask = keys.iter().fold(0u16, |mask, k| mask | key_mask(k.bit()));
+        Self::from_pressed_mask(mask)
+    }
+
+    pub fn contains(&self, key: Key) -> bool {
+        self.is_pressed(key.bit())
+    }
+
+    /// 押されているキーを表示の順で返す
+    pub fn pressed_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        Key::ALL.into_iter().filter(|&k| self.contains(k))
+    }
+
+    /// 押されているキーのビットを1にしたマスクから生成する
+    pub const fn from_pressed_mask(mask: u16) -> Self {
+        Self::new(KEY_RANGE_START | (!mask & KEY_BITS_MASK))
+    }
+
+    /// 押されているキーのビットが1のマスク (生の値とは逆の論理)
+    pub const fn pressed_mask(&self) -> u16 {
+        !self.keys & KEY_BITS_MASK
+    }
+
+    /// 同時に押しているキーの数
+    pub const fn pressed_count(&self) -> u32 {
+        self.pressed_mask().count_ones()
+    }
+
     /// 有効なキー入力かチェック
     /// 無効な組み合わせ：上下同時、左右同時、L・R・Start・Select同時
     pub const fn is_valid_raw(keys: u16) -> bool {
@@ -67,38 +197,6 @@ impl KeyPresses {
         true
     }
 
-    /// 押されているキーを文字列で返す
-    pub fn pressed_keys_string(&self) -> String {
-        let mut keys = Vec::new();
-
-        macro_rules! push {
-            ($bit:ident, $name:expr) => {
-                if (self.keys & key_mask($bit)) == 0 {
-                    keys.push($name);
-                }
-            };
-        }
-
-        push!(KEY_A_BIT, "A");
-        push!(KEY_B_BIT, "B");
-        push!(KEY_X_BIT, "X");
-        push!(KEY_Y_BIT, "Y");
-        push!(KEY_UP_BIT, "UP");
-        push!(KEY_DOWN_BIT, "DOWN");
-        push!(KEY_LEFT_BIT, "LEFT");
-        push!(KEY_RIGHT_BIT, "RIGHT");
-        push!(KEY_L_BIT, "L");
-        push!(KEY_R_BIT, "R");
-        push!(KEY_START_BIT, "START");
-        push!(KEY_SELECT_BIT, "SELECT");
-
-        if keys.is_empty() {
-            "none".to_string()
-        } else {
-            keys.join(" + ")
-        }
-    }
-    
     /// 有効なすべてのキー入力値の配列を返す（全探索用）
     pub fn iter_valid() -> impl Iterator<Item = KeyPresses> {
         (KEY_RANGE_START..=KEY_RANGE_END)
@@ -107,10 +205,139 @@ impl KeyPresses {
     }
 }
 
+/// "A + START" の形式。何も押さないときは "none"
+impl fmt::Display for KeyPresses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys = self.pressed_keys();
+        match keys.next() {
+            None => f.write_str("none"),
+            Some(first) => {
+                write!(f, "{first}")?;
+                keys.try_for_each(|k| write!(f, " + {k}"))
+            }
+        }
+    }
+}
+
+/**
+"A+START" や "a + start" のような文字列から生成する。"none" (または空文字列) は何も押さない
+
+キー名は A B X Y UP DOWN LEFT RIGHT L R START SELECT (大文字小文字は問わない)
+*/
+impl FromStr for KeyPresses {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("none") {
+            return Ok(Self::from_keys(&[]));
+        }
+        let keys = trimmed
+            .split('+')
+            .map(str::parse)
+            .collect::<Result<Vec<Key>, _>>()
+            .map_err(|_| ConfigError::InvalidKeys(trimmed.to_string()))?;
+        Ok(Self::from_keys(&keys))
+    }
+}
+
+/// 押されているキー名の配列として書き出す
+impl Serialize for KeyPresses {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.pressed_count() as usize))?;
+        for key in self.pressed_keys() {
+            seq.serialize_element(key.name())?;
+        }
+        seq.end()
+    }
+}
+
+/**
+次のどれでも読める
+- キー名の配列: `["A", "START"]`
+- "A+START" 形式の文字列
+- 生の値: `0x2ff6` のような16進文字列か整数
+*/
+impl<'de> Deserialize<'de> for KeyPresses {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = KeyPresses;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a list of key names, a \"A+START\" string or a raw hex value")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<KeyPresses, E> {
+                u16::try_from(v)
+                    .map(KeyPresses::new)
+                    .map_err(|_| E::custom(format!("key value {v} out of range for u16")))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<KeyPresses, E> {
+                u64::try_from(v)
+                    .map_err(|_| E::custom("negative key value"))
+                    .and_then(|v| self.visit_u64(v))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<KeyPresses, E> {
+                let t = s.trim();
+                if let Some(hex) = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+                    return u16::from_str_radix(hex, 16)
+                        .map(KeyPresses::new)
+                        .map_err(|e| E::custom(format!("invalid hex: {e}")));
+                }
+                t.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeyPresses, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(name) = seq.next_element::<String>()? {
+                    keys.push(name.parse::<Key>().map_err(de::Error::custom)?);
+                }
+                Ok(KeyPresses::from_keys(&keys))
+            }
+        }
+
+        deserializer.deserialize_any(V)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_roundtrips_with_display() {
+        for kp in KeyPresses::iter_valid() {
+            assert_eq!(kp.to_string().parse(), Ok(kp));
+        }
+        assert_eq!("a+Start".parse::<KeyPresses>(), "A + START".parse());
+        assert_eq!("none".parse::<KeyPresses>().unwrap().raw(), 0x2fff);
+        assert!("A+Z".parse::<KeyPresses>().is_err());
+    }
+
+    #[test]
+    fn from_keys_sets_active_low_bits() {
+        let kp = KeyPresses::from_keys(&[Key::A, Key::Start]);
+        assert_eq!(kp.raw(), 0x2ff6);
+        assert_eq!(kp.to_string(), "A + START");
+        assert_eq!(kp.pressed_keys().collect::<Vec<_>>(), [Key::A, Key::Start]);
+        assert_eq!(KeyPresses::from_keys(&[]).to_string(), "none");
+    }
+
+    #[test]
+    fn serde_accepts_list_string_and_raw() {
+        let kp = KeyPresses::from_keys(&[Key::A, Key::Start]);
+        assert_eq!(serde_json::to_string(&kp).unwrap(), r#"["A","START"]"#);
+        for json in [r#"["A","START"]"#, r#"["start","a"]"#, r#""A+START""#, r#""0x2ff6""#, "12278"] {
+            assert_eq!(serde_json::from_str::<KeyPresses>(json).unwrap(), kp, "{json}");
+        }
+        assert_eq!(serde_json::from_str::<KeyPresses>("[]").unwrap().raw(), 0x2fff);
+        assert!(serde_json::from_str::<KeyPresses>(r#"["A","Z"]"#).is_err());
+        assert!(serde_json::from_str::<KeyPresses>("70000").is_err());
+    }
+
     #[test]
     fn test_valid_keypresses_length(){
         let it = KeyPresses::iter_valid();
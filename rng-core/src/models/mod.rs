@@ -2,12 +2,22 @@ pub mod ds_config;
 pub use ds_config::*;
 pub mod game_version;
 pub use game_version::*;
+pub mod hardware;
+pub use hardware::Hardware;
+pub mod ds_date_time;
+pub use ds_date_time::DsDateTime;
+pub mod date_set;
+pub use date_set::{DateSet, Season};
 pub mod game_date;
 pub mod game_date_iterator;
 pub mod game_time;
 pub use game_time::*;
 pub mod key_presses;
 pub use key_presses::*;
+pub mod key_press_filter;
+pub use key_press_filter::*;
+pub mod time_set;
+pub use time_set::{TimeOfDay, TimeSet};
 pub mod game_time_iterator;
 pub use game_time_iterator::*;
 pub mod field_range;
@@ -0,0 +1,214 @@
+/*!
+検索する時刻の集合
+
+時・分・秒それぞれの値の組み合わせ (飛び飛びでもよい) と、時刻の窓の和集合で表す
+終わりが始まりより前の窓は 0 時をまたぎ、またいだ後の時刻は翌日の日付で計算する
+
+GPU には [`TimeSet::packed`] の一覧を渡す。time9 の下位 8 ビットは常に 0 なので、そこに翌日かどうかを入れている
+*/
+
+use crate::error::ConfigError;
+use crate::models::ds_date_time;
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// 検索する時刻ひとつ。`day_offset` が 1 なら基準の日付の翌日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    pub day_offset: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl TimeOfDay {
+    fn from_seconds(day_offset: u8, secs: u32) -> Self {
+        Self {
+            day_offset,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+        }
+    }
+
+    pub fn time9(&self) -> u32 {
+        ds_date_time::time9(self.hour, self.minute, self.second)
+    }
+
+    /// GPU に渡す形 (time9 の下位 8 ビットに `day_offset`)
+    pub fn packed(&self) -> u32 {
+        self.time9() | self.day_offset as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TimePart {
+    /// 時・分・秒の値の直積 (ビットが立っている値を使う)
+    Grid { hours: u32, minutes: u64, seconds: u64 },
+    /// 0 時からの秒で表した両端を含む窓。`end < start` なら翌日へまたぐ
+    Window { start: u32, end: u32 },
+}
+
+/// 時刻の集合
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSet {
+    parts: Vec<TimePart>,
+}
+
+fn mask<T: Into<u32>>(values: impl IntoIterator<Item = T>, limit: u32) -> u64 {
+    values
+        .into_iter()
+        .map(Into::into)
+        .filter(|&v| v < limit)
+        .fold(0u64, |m, v| m | (1u64 << v))
+}
+
+fn seconds_of_day((hour, minute, second): (u8, u8, u8)) -> Result<u32, ConfigError> {
+    if hour >= 24 || minute >= 60 || second >= 60 {
+        return Err(ConfigError::InvalidTime { hour, minute, second });
+    }
+    Ok(hour as u32 * 3600 + minute as u32 * 60 + second as u32)
+}
+
+impl TimeSet {
+    /// 1日のすべての時刻
+    pub fn all() -> Self {
+        Self::grid(0..24u8, 0..60u8, 0..60u8)
+    }
+
+    pub fn empty() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /**
+    時・分・秒それぞれに使う値を並べる。範囲外の値は無視する
+
+    "10-20 秒と 40-50 秒だけ" なら `TimeSet::grid(0..24, 0..60, (10..=20).chain(40..=50))`
+    */
+    pub fn grid(
+        hours: impl IntoIterator<Item = u8>,
+        minutes: impl IntoIterator<Item = u8>,
+        seconds: impl IntoIterator<Item = u8>,
+    ) -> Self {
+        Self {
+            parts: vec![TimePart::Grid {
+                hours: mask(hours, 24) as u32,
+                minutes: mask(minutes, 60),
+                seconds: mask(seconds, 60),
+            }],
+        }
+    }
+
+    /// 時・分・秒の `[min, max]` の箱 (これまでの GPU 入力と同じ指定)。min > max の軸があれば空
+    pub fn from_ranges(hour_range: [u32; 2], minute_range: [u32; 2], second_range: [u32; 2]) -> Self {
+        let values = |r: [u32; 2]| (r[0]..=r[1]).filter_map(|v| u8::try_from(v).ok());
+        Self::grid(values(hour_range), values(minute_range), values(second_range))
+    }
+
+    /// `start` から `end` までの窓 (両端を含む)。`end` が `start` より前なら 0 時をまたいで翌日の `end` まで
+    pub fn window(start: (u8, u8, u8), end: (u8, u8, u8)) -> Result<Self, ConfigError> {
+        Ok(Self {
+            parts: vec![TimePart::Window {
+                start: seconds_of_day(start)?,
+                end: seconds_of_day(end)?,
+            }],
+        })
+    }
+
+    pub fn union(mut self, other: TimeSet) -> Self {
+        self.parts.extend(other.parts);
+        self
+    }
+
+    /// 含まれる時刻を (翌日かどうか, 時刻) の順に重複なく返す
+    pub fn times(&self) -> Vec<TimeOfDay> {
+        // 当日と翌日の 0 時からの秒ごとのフラグ
+        let mut hit = vec![false; 2 * SECONDS_PER_DAY as usize];
+        for part in &self.parts {
+            match *part {
+                TimePart::Grid { hours, minutes, seconds } => {
+                    for h in (0..24).filter(|h| hours >> h & 1 == 1) {
+                        for m in (0..60).filter(|m| minutes >> m & 1 == 1) {
+                            for s in (0..60).filter(|s| seconds >> s & 1 == 1) {
+                                hit[(h * 3600 + m * 60 + s) as usize] = true;
+                            }
+                        }
+                    }
+                }
+                TimePart::Window { start, end } => {
+                    let end = if end < start { end + SECONDS_PER_DAY } else { end };
+                    hit[start as usize..=end as usize].fill(true);
+                }
+            }
+        }
+        hit.iter()
+            .enumerate()
+            .filter(|&(_, &h)| h)
+            .map(|(i, _)| {
+                let i = i as u32;
+                TimeOfDay::from_seconds((i / SECONDS_PER_DAY) as u8, i % SECONDS_PER_DAY)
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.times().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// GPU に渡す時刻の一覧 ([`TimeOfDay::packed`])
+    pub fn packed(&self) -> Vec<u32> {
+        self.times().iter().map(TimeOfDay::packed).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_match_the_old_box() {
+        let set = TimeSet::from_ranges([10, 11], [0, 59], [58, 59]);
+        let times = set.times();
+        assert_eq!(times.len(), 2 * 60 * 2);
+        assert_eq!(times[0], TimeOfDay { day_offset: 0, hour: 10, minute: 0, second: 58 });
+        assert!(TimeSet::from_ranges([5, 4], [0, 59], [0, 59]).is_empty());
+        assert_eq!(TimeSet::all().len(), 86400);
+    }
+
+    #[test]
+    fn discontinuous_seconds() {
+        let set = TimeSet::grid([12], [0], (10..=20).chain(40..=50));
+        let secs: Vec<u8> = set.times().iter().map(|t| t.second).collect();
+        assert_eq!(secs.len(), 22);
+        assert!(secs.iter().all(|s| (10..=20).contains(s) || (40..=50).contains(s)));
+    }
+
+    #[test]
+    fn window_wraps_midnight_into_the_next_day() {
+        let set = TimeSet::window((23, 30, 0), (0, 30, 0)).unwrap();
+        let times = set.times();
+        assert_eq!(times.len(), 30 * 60 + 30 * 60 + 1);
+        assert_eq!(times.first(), Some(&TimeOfDay { day_offset: 0, hour: 23, minute: 30, second: 0 }));
+        assert_eq!(times.last(), Some(&TimeOfDay { day_offset: 1, hour: 0, minute: 30, second: 0 }));
+        assert_eq!(times.iter().filter(|t| t.day_offset == 1).count(), 30 * 60 + 1);
+        assert!(TimeSet::window((24, 0, 0), (1, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn union_removes_duplicates() {
+        let a = TimeSet::window((10, 0, 0), (10, 0, 59)).unwrap();
+        let b = TimeSet::grid([10], [0, 1], [0]);
+        assert_eq!(a.union(b).len(), 61);
+    }
+
+    #[test]
+    fn packed_keeps_time9_and_day_offset() {
+        let t = TimeOfDay { day_offset: 1, hour: 13, minute: 5, second: 9 };
+        assert_eq!(t.packed(), 0x53050901);
+        assert_eq!(t.packed() & !0xff, t.time9());
+    }
+}
@@ -1,3 +1,5 @@
+use infra::file::CsvRecord;
+
 use crate::models::{DSConfig, GameTime, KeyPresses};
 
 #[derive(Debug, Clone)]
@@ -9,3 +11,55 @@ pub struct ResultBase {
     pub key_presses: KeyPresses,
     pub ivs: [u8; 6],
 }
+
+impl CsvRecord for ResultBase {
+    fn header() -> &'static [&'static str] {
+        &[
+            "seed0", "seed1",
+            "year", "month", "day", "hour", "minute", "second",
+            "timer0", "key_presses",
+            "iv_h", "iv_a", "iv_b", "iv_c", "iv_d", "iv_s",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut row = vec![
+            format!("0x{:016X}", self.seed0),
+            format!("0x{:016X}", self.seed1),
+            self.game_time.year().to_string(),
+            self.game_time.month().to_string(),
+            self.game_time.day().to_string(),
+            self.game_time.hour().to_string(),
+            self.game_time.minute().to_string(),
+            self.game_time.second().to_string(),
+            format!("0x{:X}", self.ds_config.Timer0),
+            self.key_presses.to_string(),
+        ];
+        row.extend(self.ivs.iter().map(|iv| iv.to_string()));
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GameVersion, Hardware};
+
+    #[test]
+    fn csv_row_matches_header() {
+        let base = ResultBase {
+            ds_config: DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce),
+            seed0: 0x9B3E7C4BC185AE31,
+            seed1: 0xA90C98ED53739118,
+            game_time: GameTime::new(33, 8, 27, 12, 34, 56),
+            key_presses: KeyPresses::new(0x2ff6),
+            ivs: [31, 19, 31, 31, 31, 31],
+        };
+        let row = base.row();
+        assert_eq!(row.len(), ResultBase::header().len());
+        assert_eq!(row[0], "0x9B3E7C4BC185AE31");
+        assert_eq!(row[8], "0x10FA");
+        assert_eq!(row[9], "A + START");
+        assert_eq!(&row[10..], ["31", "19", "31", "31", "31", "31"]);
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use infra::file::CsvRecord;
+use infra::gpu::GpuError;
 use infra::gpu::context::GpuContext;
 use rng_core::gpu::helpers::{GpuInputParams, run_result_base_seedhigh_by_dates};
 use rng_core::lcg::{Lcg, OffsetType};
@@ -9,7 +11,13 @@ use rng_core::models::game_date::GameDate;
 use rng_core::models::*;
 use rng_core::result_base::ResultBase;
 
-const TARGET_DATES: [(u8, u8); 6] = [
+use crate::error::SearchError;
+use crate::progress::{ProgressTracker, SearchControl};
+use crate::robustness::{self, Neighbourhood, Robustness};
+use crate::sink::ResultSink;
+
+/// 既定で探す月日 (毎年)
+const TARGET_MONTH_DAYS: [(u8, u8); 6] = [
     (4, 29),
     (4, 30),
     (8, 30),
@@ -18,6 +26,14 @@ const TARGET_DATES: [(u8, u8); 6] = [
     (12, 31),
 ];
 
+const IV_MIN: [u8; 6] = [30, 31, 30, 0, 30, 31];
+const IV_MAX: [u8; 6] = [31, 31, 31, 31, 31, 31];
+
+/// `dates` を指定しないときに探す日付
+pub fn target_dates() -> DateSet {
+    DateSet::month_days(&TARGET_MONTH_DAYS)
+}
+
 #[derive(Debug, Clone)]
 pub struct PupSearchResult {
     pub seed0: u64,
@@ -31,18 +47,67 @@ pub struct PupSearchResult {
     pub key_presses: KeyPresses,
     pub ivs: [u8; 6],
     pub wild_advances: Vec<u32>,
+    /// [`robustness`] で計算したときだけ入る
+    pub robustness: Option<Robustness>,
 }
 
+impl CsvRecord for PupSearchResult {
+    fn header() -> &'static [&'static str] {
+        &[
+            "seed0", "seed1",
+            "year", "month", "day", "hour", "minute", "second",
+            "key_presses",
+            "iv_h", "iv_a", "iv_b", "iv_c", "iv_d", "iv_s",
+            "wild_advances",
+            "score", "neighbourhood",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut row = vec![
+            format!("0x{:016X}", self.seed0),
+            format!("0x{:016X}", self.seed1),
+            self.year.to_string(),
+            self.month.to_string(),
+            self.day.to_string(),
+            self.hour.to_string(),
+            self.minute.to_string(),
+            self.second.to_string(),
+            self.key_presses.to_string(),
+        ];
+        row.extend(self.ivs.iter().map(|iv| iv.to_string()));
+        row.push(
+            self.wild_advances
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(";"),
+        );
+        row.push(self.robustness.as_ref().map(|r| format!("{:.4}", r.score)).unwrap_or_default());
+        row.push(self.robustness.as_ref().map(Robustness::map).unwrap_or_default());
+        row
+    }
+}
 
 const BATCH_DATES: usize = 256;
 
-pub async fn search(ds_config: DSConfig, wild_max_advances: u32) -> Vec<PupSearchResult> {
-    let ctx = GpuContext::new().await;
+pub async fn search(ds_config: DSConfig, wild_max_advances: u32) -> Result<Vec<PupSearchResult>, SearchError> {
     let mut results = Vec::new();
-    let mut seen_seed0: HashSet<u64> = HashSet::new();
+    search_with_sink(ds_config, &target_dates(), wild_max_advances, &KeyPressFilter::all(), &mut results, &mut SearchControl::new()).await?;
+    Ok(results)
+}
 
-    let iv_min: [u32; 6] = [30, 31, 30, 0, 30, 31];
-    let iv_max: [u32; 6] = [31, 31, 31, 31, 31, 31];
+/// 見つかった結果を順次 `sink` に流す版。探す日付は `dates` (既定は [`target_dates`])
+pub async fn search_with_sink(
+    ds_config: DSConfig,
+    dates: &DateSet,
+    wild_max_advances: u32,
+    key_filter: &KeyPressFilter,
+    sink: &mut impl ResultSink<PupSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let ctx = GpuContext::new().await?;
+    let mut seen_seed0: HashSet<u64> = HashSet::new();
 
     let params = GpuInputParams::new(
         ds_config,
@@ -50,40 +115,39 @@ pub async fn search(ds_config: DSConfig, wild_max_advances: u32) -> Vec<PupSearc
         [0, 59],
         [0, 59],
         0,
-        iv_min,
-        iv_max,
-    );
-
-    let mut dates = Vec::with_capacity(BATCH_DATES);
-    for year in 0..=99u8 {
-        for &(month, day) in &TARGET_DATES {
-            dates.push(GameDate { year, month, day });
-            if dates.len() >= BATCH_DATES {
-                collect_gpu_results(&ctx, ds_config, wild_max_advances, &params, &dates, &mut results, &mut seen_seed0).await;
-                dates.clear();
-            }
+        IV_MIN.map(u32::from),
+        IV_MAX.map(u32::from),
+    )
+    .with_result_limit(control.candidate_limit())
+    .with_key_filter(key_filter.clone());
+
+    let dates = dates.dates();
+    let mut tracker = control.tracker(dates.len(), params.seeds_per_date());
+
+    for date_batch in dates.chunks(BATCH_DATES) {
+        if control.is_cancelled() {
+            break;
         }
+        collect_gpu_results(&ctx, ds_config, wild_max_advances, &params, date_batch, sink, &mut seen_seed0, &mut tracker).await?;
+        control.check_candidates(&tracker)?;
+        control.report(&mut tracker);
     }
-    if !dates.is_empty() {
-        collect_gpu_results(&ctx, ds_config, wild_max_advances, &params, &dates, &mut results, &mut seen_seed0).await;
-    }
-
-    results
+    Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn collect_gpu_results(
     ctx: &GpuContext,
     ds_config: DSConfig,
     wild_max_advances: u32,
     params: &GpuInputParams,
     dates: &[GameDate],
-    results: &mut Vec<PupSearchResult>,
+    sink: &mut impl ResultSink<PupSearchResult>,
     seen_seed0: &mut HashSet<u64>,
-) {
-    let base_results = match run_result_base_seedhigh_by_dates(ctx, ds_config, params, dates, BATCH_DATES).await {
-        Ok(v) => v,
-        Err(_) => return,
-    };
+    tracker: &mut ProgressTracker,
+) -> Result<(), GpuError> {
+    let base_results = run_result_base_seedhigh_by_dates(ctx, ds_config, params, dates, BATCH_DATES).await?;
+    tracker.dates_done(dates.len(), base_results.len());
 
     for base in base_results.into_iter() {
         let ResultBase {
@@ -94,28 +158,45 @@ async fn collect_gpu_results(
             ivs,
             ..
         } = base;
-        if !seen_seed0.insert(seed0) {
-            continue;
-        }
-
         let wild_advances = find_wild_poke_advances(seed0, wild_max_advances);
         if wild_advances.is_empty() {
             continue;
         }
-        results.push(PupSearchResult {
+        // 重複排除はヒットした seed0 だけを覚える
+        if !seen_seed0.insert(seed0) {
+            continue;
+        }
+        sink.emit(PupSearchResult {
             seed0,
             seed1,
-            year: game_time.year as u16,
-            month: game_time.month,
-            day: game_time.day,
-            hour: game_time.hour,
-            minute: game_time.minute,
-            second: game_time.second,
+            year: game_time.year() as u16,
+            month: game_time.month(),
+            day: game_time.day(),
+            hour: game_time.hour(),
+            minute: game_time.minute(),
+            second: game_time.second(),
             key_presses,
             ivs,
             wild_advances,
+            robustness: None,
         });
+        tracker.results_found(1);
     }
+    Ok(())
+}
+
+/// `result` の前後の秒・他の Timer0・キーの押し間違いでも、IV と野生の条件を満たすかを調べる
+pub fn robustness(
+    ds_config: DSConfig,
+    result: &PupSearchResult,
+    wild_max_advances: u32,
+    neighbourhood: &Neighbourhood,
+) -> Robustness {
+    let game_time = GameTime::new(result.year as u8, result.month, result.day, result.hour, result.minute, result.second);
+    robustness::evaluate(ds_config, &game_time, result.key_presses, neighbourhood, 0, |base| {
+        let ivs_ok = (0..6).all(|i| (IV_MIN[i]..=IV_MAX[i]).contains(&base.ivs[i]));
+        ivs_ok && !find_wild_poke_advances(base.seed0, wild_max_advances).is_empty()
+    })
 }
 
 fn find_wild_poke_advances(seed0: u64, max_advances: u32) -> Vec<u32> {
@@ -146,17 +227,40 @@ mod tests {
     use std::time::Instant;
     use super::*;
 
+    #[test]
+    fn csv_row_joins_wild_advances() {
+        let r = PupSearchResult {
+            seed0: 0x45758423BB8FCDB8,
+            seed1: 0,
+            year: 11,
+            month: 4,
+            day: 29,
+            hour: 1,
+            minute: 2,
+            second: 3,
+            key_presses: KeyPresses::new(0x2fff),
+            ivs: [30, 31, 30, 12, 30, 31],
+            wild_advances: vec![42, 57],
+            robustness: None,
+        };
+        let row = r.row();
+        assert_eq!(row.len(), PupSearchResult::header().len());
+        assert_eq!(row[8], "none");
+        assert_eq!(row[15], "42;57");
+        assert_eq!(row[16], "");
+    }
+
     #[test]
     #[ignore]
     fn test_black1_pups() {
         let ds_config = DSConfig{
             Version : GameVersion::Black,
             Timer0 : 0xc7a,
-            IsDSLite : false,
+            Hardware: Hardware::DsPhat,
             MAC : 0x9bf6d93ce,
         };
         let start = Instant::now();
-        let results = pollster::block_on(async { search(ds_config, 70).await });
+        let results = pollster::block_on(async { search(ds_config, 70).await }).expect("search failed");
         let elapsed = start.elapsed();
 
         println!("Elapsed: {:?}", elapsed);
@@ -172,7 +276,7 @@ mod tests {
                 r.hour,
                 r.minute,
                 r.second,
-                r.key_presses.pressed_keys_string(),
+                r.key_presses.to_string(),
                 r.ivs,
                 r.wild_advances
             );
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use infra::gpu::GpuError;
+use rng_core::error::ConfigError;
+
+/// 検索関数が返すエラー
+#[derive(Debug)]
+pub enum SearchError {
+    /// 日付などの入力が不正
+    Config(ConfigError),
+    /// GPU の初期化やバッチの実行に失敗した
+    Gpu(GpuError),
+    /// IV プリセットのない性格
+    UnsupportedNature(u8),
+    /// IV の下限が上限を超えている、または 31 を超えている
+    InvalidIvRange { iv_min: [u8; 6], iv_max: [u8; 6] },
+    /// 夏 (month % 4 == 2) の日付は季節が変わるので検索できない
+    SummerDate { month: u8 },
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Config(e) => write!(f, "{e}"),
+            SearchError::Gpu(e) => write!(f, "{e}"),
+            SearchError::UnsupportedNature(id) => {
+                write!(f, "nature id {id} has no IV preset; specify IV bounds explicitly")
+            }
+            SearchError::InvalidIvRange { iv_min, iv_max } => {
+                write!(f, "invalid IV range {:?}..={:?}", iv_min, iv_max)
+            }
+            SearchError::SummerDate { month } => {
+                write!(f, "month {month} is summer; summer dates are not supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SearchError::Config(e) => Some(e),
+            SearchError::Gpu(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for SearchError {
+    fn from(e: ConfigError) -> Self {
+        SearchError::Config(e)
+    }
+}
+
+impl From<GpuError> for SearchError {
+    fn from(e: GpuError) -> Self {
+        SearchError::Gpu(e)
+    }
+}
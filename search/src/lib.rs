@@ -1,2 +1,7 @@
 pub mod black1_pup;
+pub mod error;
+pub mod progress;
+pub mod robustness;
+pub mod sink;
+pub mod starter;
 pub mod white2_tepig;
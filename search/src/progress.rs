@@ -0,0 +1,223 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use infra::gpu::GpuError;
+
+/// 検索の途中経過
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchProgress {
+    pub dates_done: usize,
+    pub dates_total: usize,
+    /// GPU の IV フィルタを通った候補数
+    pub candidates: usize,
+    /// sink に流した結果数
+    pub results: usize,
+    /// SHA-1 を計算した seed0 の数
+    pub seeds_searched: u64,
+    pub elapsed: Duration,
+}
+
+impl SearchProgress {
+    pub fn seeds_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+        self.seeds_searched as f64 / secs
+    }
+
+    /// 残り時間の見積もり。まだ1日も終わっていなければ None
+    pub fn eta(&self) -> Option<Duration> {
+        if self.dates_done == 0 {
+            return None;
+        }
+        let remaining = self.dates_total.saturating_sub(self.dates_done);
+        Some(self.elapsed.mul_f64(remaining as f64 / self.dates_done as f64))
+    }
+
+    pub fn fraction(&self) -> f64 {
+        if self.dates_total == 0 {
+            return 1.0;
+        }
+        self.dates_done as f64 / self.dates_total as f64
+    }
+}
+
+/**
+検索の中断フラグ
+
+clone したものは同じフラグを共有するので、
+Ctrl-C ハンドラや UI スレッドに渡して `cancel` を呼べばよい
+検索側はバッチの合間にしか見ないので、止まるのは実行中のバッチが終わってから
+*/
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+type ProgressCallback<'a> = Box<dyn FnMut(&SearchProgress) + 'a>;
+
+/// 検索関数に渡す進捗コールバックと中断フラグ
+#[derive(Default)]
+pub struct SearchControl<'a> {
+    cancel: CancelToken,
+    on_progress: Option<ProgressCallback<'a>>,
+    candidate_limit: Option<usize>,
+}
+
+impl<'a> SearchControl<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// バッチが終わるたびに呼ばれる
+    pub fn on_progress(mut self, f: impl FnMut(&SearchProgress) + 'a) -> Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /**
+    GPU の IV フィルタを通った候補数の上限 (検索全体での合計)
+
+    条件がゆるすぎて候補が多すぎるときに、黙って打ち切る代わりに
+    `GpuError::Truncated` で止めるためのもの。既定は上限なし
+    */
+    pub fn with_candidate_limit(mut self, limit: Option<usize>) -> Self {
+        self.candidate_limit = limit;
+        self
+    }
+
+    pub fn candidate_limit(&self) -> Option<usize> {
+        self.candidate_limit
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    pub(crate) fn tracker(&self, dates_total: usize, seeds_per_date: u64) -> ProgressTracker {
+        ProgressTracker {
+            start: Instant::now(),
+            seeds_per_date,
+            progress: SearchProgress {
+                dates_done: 0,
+                dates_total,
+                candidates: 0,
+                results: 0,
+                seeds_searched: 0,
+                elapsed: Duration::ZERO,
+            },
+        }
+    }
+
+    pub(crate) fn check_candidates(&self, tracker: &ProgressTracker) -> Result<(), GpuError> {
+        match self.candidate_limit {
+            Some(limit) if tracker.progress.candidates > limit => Err(GpuError::Truncated { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn report(&mut self, tracker: &mut ProgressTracker) {
+        tracker.progress.elapsed = tracker.start.elapsed();
+        if let Some(f) = self.on_progress.as_mut() {
+            f(&tracker.progress);
+        }
+    }
+}
+
+/// 検索関数の中で進捗を数える
+pub(crate) struct ProgressTracker {
+    start: Instant,
+    seeds_per_date: u64,
+    progress: SearchProgress,
+}
+
+impl ProgressTracker {
+    pub(crate) fn dates_done(&mut self, n: usize, candidates: usize) {
+        self.progress.dates_done += n;
+        self.progress.candidates += candidates;
+        self.progress.seeds_searched += n as u64 * self.seeds_per_date;
+    }
+
+    pub(crate) fn results_found(&mut self, n: usize) {
+        self.progress.results += n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eta_scales_with_remaining_dates() {
+        let p = SearchProgress {
+            dates_done: 25,
+            dates_total: 100,
+            candidates: 0,
+            results: 0,
+            seeds_searched: 1000,
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(p.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(p.seeds_per_sec(), 100.0);
+        assert_eq!(p.fraction(), 0.25);
+    }
+
+    #[test]
+    fn cancel_is_shared_between_clones() {
+        let token = CancelToken::new();
+        let control = SearchControl::new().with_cancel(token.clone());
+        assert!(!control.is_cancelled());
+        token.cancel();
+        assert!(control.is_cancelled());
+    }
+
+    #[test]
+    fn candidate_limit_is_checked_against_total() {
+        let control = SearchControl::new().with_candidate_limit(Some(5));
+        let mut tracker = control.tracker(10, 7);
+        tracker.dates_done(1, 3);
+        assert!(control.check_candidates(&tracker).is_ok());
+        tracker.dates_done(1, 3);
+        assert!(matches!(
+            control.check_candidates(&tracker),
+            Err(GpuError::Truncated { limit: 5 })
+        ));
+        assert!(SearchControl::new().check_candidates(&tracker).is_ok());
+    }
+
+    #[test]
+    fn report_calls_callback_with_totals() {
+        let mut seen = Vec::new();
+        {
+            let mut control = SearchControl::new().on_progress(|p| seen.push(*p));
+            let mut tracker = control.tracker(10, 7);
+            tracker.dates_done(4, 2);
+            tracker.results_found(1);
+            control.report(&mut tracker);
+        }
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].dates_done, 4);
+        assert_eq!(seen[0].candidates, 2);
+        assert_eq!(seen[0].results, 1);
+        assert_eq!(seen[0].seeds_searched, 28);
+    }
+}
@@ -0,0 +1,258 @@
+/*!
+ヒットした seed の取りやすさ
+
+ちょうど 12:34:56、Timer0 0x10FA のときだけ当たる seed より、前後の秒や他の Timer0、
+キーの押し間違いでも当たる seed の方が実機で再現しやすい。
+結果ごとに近傍の seed0 を計算し直して検索と同じ判定にかけ、当たる確率と内訳を出す
+*/
+
+use std::ops::RangeInclusive;
+
+use rng_core::lcg::lcg_next;
+use rng_core::models::{DSConfig, DsDateTime, GameTime, Key, KeyPresses};
+use rng_core::mt;
+use rng_core::result_base::ResultBase;
+use rng_core::sha_1::Seed0Hasher;
+
+/// 前後にずらせる秒数の上限 (`i8` に収まる範囲)
+pub const MAX_SECONDS: u8 = i8::MAX as u8;
+
+/// どこまでずれうるかの想定
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbourhood {
+    /// 前後に何秒ずれうるか。ずれの確率は真ん中を頂点にした三角形
+    pub seconds: u8,
+    /// 起動ごとにぶれる Timer0 の範囲 (両端を含む)。どれも同じ確率とする
+    pub timer0: RangeInclusive<u16>,
+    /// キーを1つ押し損ねる・余分に押す確率
+    pub key_slip_rate: f64,
+}
+
+impl Neighbourhood {
+    /// ±1 秒、Timer0 はプロファイルの値だけ、キーの押し間違いは 1 割
+    pub fn new(config: &DSConfig) -> Self {
+        Self {
+            seconds: 1,
+            timer0: config.Timer0..=config.Timer0,
+            key_slip_rate: 0.1,
+        }
+    }
+
+    /// [`NeighbourCell::second_offset`] に入るよう [`MAX_SECONDS`] までに抑える
+    pub fn with_seconds(mut self, seconds: u8) -> Self {
+        self.seconds = seconds.min(MAX_SECONDS);
+        self
+    }
+
+    pub fn with_timer0(mut self, timer0: RangeInclusive<u16>) -> Self {
+        self.timer0 = timer0;
+        self
+    }
+
+    pub fn with_key_slip_rate(mut self, rate: f64) -> Self {
+        self.key_slip_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    fn second_weight(&self, offset: i32) -> f64 {
+        let s = self.seconds.min(MAX_SECONDS) as f64 + 1.0;
+        (s - offset.unsigned_abs() as f64) / (s * s)
+    }
+}
+
+/// 近傍の1点 (秒のずれ × Timer0 × キー入力)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeighbourCell {
+    pub second_offset: i8,
+    pub timer0: u16,
+    pub key_presses: KeyPresses,
+    /// 狙ったキー入力そのものなら false
+    pub key_slip: bool,
+    /// この組み合わせになる確率
+    pub weight: f64,
+    pub hit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Robustness {
+    /// 狙って操作したときに当たる確率 (0.0 - 1.0)
+    pub score: f64,
+    pub cells: Vec<NeighbourCell>,
+}
+
+impl Robustness {
+    /**
+    狙ったキー入力での当たり外れを Timer0 ごとに秒の順で並べ、押し間違いは件数だけ出す
+
+    `10F9 .#. | 10FA ### | 10FB ..# | slips 3/66`
+    */
+    pub fn map(&self) -> String {
+        let mut timer0s: Vec<u16> = self.cells.iter().map(|c| c.timer0).collect();
+        timer0s.dedup();
+        let mut parts: Vec<String> = timer0s
+            .iter()
+            .map(|&t| {
+                let marks: String = self
+                    .cells
+                    .iter()
+                    .filter(|c| c.timer0 == t && !c.key_slip)
+                    .map(|c| if c.hit { '#' } else { '.' })
+                    .collect();
+                format!("{:X} {}", t, marks)
+            })
+            .collect();
+        let slips = self.cells.iter().filter(|c| c.key_slip);
+        let total = slips.clone().count();
+        if total > 0 {
+            parts.push(format!("slips {}/{}", slips.filter(|c| c.hit).count(), total));
+        }
+        parts.join(" | ")
+    }
+}
+
+/// 1つだけ押し損ねた・余分に押した組み合わせ (同時押しできないものは除く)
+pub fn key_slips(key_presses: KeyPresses) -> Vec<KeyPresses> {
+    let mask = key_presses.pressed_mask();
+    Key::ALL
+        .iter()
+        .map(|k| KeyPresses::from_pressed_mask(mask ^ (1 << k.bit())))
+        .filter(|k| KeyPresses::is_valid_raw(k.raw()))
+        .collect()
+}
+
+/**
+`game_time` と `key_presses` を狙ったときに、近傍の seed でも `is_hit` が通るかを調べる
+
+近傍の seed は `ResultBase` にして渡す。IV は `iv_step` で計算する
+2000-2099 年から外れる時刻は外れとして数える
+*/
+pub fn evaluate(
+    config: DSConfig,
+    game_time: &GameTime,
+    key_presses: KeyPresses,
+    neighbourhood: &Neighbourhood,
+    iv_step: u8,
+    is_hit: impl Fn(&ResultBase) -> bool,
+) -> Robustness {
+    let center = DsDateTime::try_from(*game_time).ok();
+    let slips = key_slips(key_presses);
+    let slip_rate = if slips.is_empty() { 0.0 } else { neighbourhood.key_slip_rate };
+    let mut keys = vec![(key_presses, false, 1.0 - slip_rate)];
+    keys.extend(slips.iter().map(|&k| (k, true, slip_rate / slips.len() as f64)));
+    let key_list: Vec<KeyPresses> = keys.iter().map(|&(k, _, _)| k).collect();
+
+    let timer0_count = neighbourhood.timer0.clone().count().max(1) as f64;
+    let radius = i8::try_from(neighbourhood.seconds).unwrap_or(i8::MAX);
+
+    let mut cells = Vec::new();
+    let mut score = 0.0;
+    for timer0 in neighbourhood.timer0.clone() {
+        let ds_config = DSConfig { Timer0: timer0, ..config };
+        let hasher = Seed0Hasher::new(&ds_config);
+        for offset in -radius..=radius {
+            let time = center.and_then(|t| t.checked_add_seconds(offset.into()));
+            // 日付・時刻が同じなので、狙ったキー入力と押し間違いは途中状態を使い回して計算する
+            let mut seeds = Vec::with_capacity(key_list.len());
+            if let Some(t) = time {
+                hasher.prefix(t.date8(), t.time9()).seed0_keys(&key_list, &mut seeds);
+            }
+            let time = time.map(GameTime::from);
+            for (i, &(keys, key_slip, key_weight)) in keys.iter().enumerate() {
+                let weight = neighbourhood.second_weight(offset.into()) / timer0_count * key_weight;
+                let hit = time.is_some_and(|game_time| {
+                    let seed0 = seeds[i];
+                    let seed1 = lcg_next(seed0);
+                    is_hit(&ResultBase {
+                        ds_config,
+                        seed0,
+                        seed1,
+                        game_time,
+                        key_presses: keys,
+                        ivs: mt::mt_1(seed1, iv_step),
+                    })
+                });
+                if hit {
+                    score += weight;
+                }
+                cells.push(NeighbourCell {
+                    second_offset: offset,
+                    timer0,
+                    key_presses: keys,
+                    key_slip,
+                    weight,
+                    hit,
+                });
+            }
+        }
+    }
+    Robustness { score, cells }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng_core::models::{GameVersion, Hardware};
+    use rng_core::sha_1::generate_initial_seed0;
+
+    fn config() -> DSConfig {
+        DSConfig::new(GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce)
+    }
+
+    #[test]
+    fn weights_sum_to_one() {
+        let n = Neighbourhood::new(&config()).with_seconds(2).with_timer0(0x10F9..=0x10FB);
+        let r = evaluate(config(), &GameTime::new(33, 8, 27, 12, 34, 56), KeyPresses::new(0x2ff6), &n, 0, |_| true);
+        assert!((r.score - 1.0).abs() < 1e-9);
+        let slips = key_slips(KeyPresses::new(0x2ff6)).len();
+        assert_eq!(r.cells.len(), 5 * 3 * (1 + slips));
+    }
+
+    #[test]
+    fn only_the_exact_seed_hits() {
+        let cfg = config();
+        let time = GameTime::new(33, 8, 27, 12, 34, 56);
+        let keys: KeyPresses = "A + START".parse().unwrap();
+        let target = generate_initial_seed0(&cfg, &time, keys);
+        let n = Neighbourhood::new(&cfg).with_timer0(0x10F9..=0x10FB);
+
+        let r = evaluate(cfg, &time, keys, &n, 0, |base| base.seed0 == target);
+        // 真ん中の秒 1/2 × Timer0 1/3 × 押し間違えない 0.9
+        assert!((r.score - 0.5 / 3.0 * 0.9).abs() < 1e-9, "{}", r.score);
+        assert_eq!(r.cells.iter().filter(|c| c.hit).count(), 1);
+        assert!(r.map().starts_with("10F9 ... | 10FA .#. | 10FB ... | slips 0/"), "{}", r.map());
+    }
+
+    #[test]
+    fn neighbours_cross_midnight() {
+        let cfg = config();
+        let n = Neighbourhood::new(&cfg);
+        let r = evaluate(cfg, &GameTime::new(33, 8, 31, 23, 59, 59), KeyPresses::new(0x2fff), &n, 0, |base| {
+            base.game_time == GameTime::new(33, 9, 1, 0, 0, 0)
+        });
+        assert!(r.cells.iter().any(|c| c.hit && c.second_offset == 1));
+
+        let last = evaluate(cfg, &GameTime::new(99, 12, 31, 23, 59, 59), KeyPresses::new(0x2fff), &n, 0, |_| true);
+        assert!(last.cells.iter().filter(|c| c.second_offset == 1).all(|c| !c.hit));
+    }
+
+    #[test]
+    fn seconds_are_capped_to_fit_the_offset() {
+        let cfg = config();
+        let n = Neighbourhood::new(&cfg).with_seconds(200);
+        assert_eq!(n.seconds, MAX_SECONDS);
+        let r = evaluate(cfg, &GameTime::new(33, 8, 27, 12, 34, 56), KeyPresses::new(0x2fff), &n, 0, |_| true);
+        let offsets: Vec<i8> = r.cells.iter().filter(|c| !c.key_slip).map(|c| c.second_offset).collect();
+        assert_eq!(offsets.first(), Some(&-127));
+        assert_eq!(offsets.last(), Some(&127));
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn slips_skip_impossible_combinations() {
+        let up: KeyPresses = "UP".parse().unwrap();
+        let slips = key_slips(up);
+        assert!(slips.contains(&KeyPresses::new(0x2fff)));
+        assert!(!slips.iter().any(|k| k.contains(Key::Up) && k.contains(Key::Down)));
+        assert_eq!(slips.len(), 11);
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::mpsc::{Sender, SyncSender};
+
+/**
+検索結果を見つかった順に1件ずつ受け取る出力先
+
+検索関数は結果を溜め込まずにここへ流すので、
+長い検索でもヒットをすぐに表示・保存できる
+*/
+pub trait ResultSink<T> {
+    fn emit(&mut self, result: T);
+}
+
+/// 全件をメモリに集める(従来の `Vec` を返す API 用)
+impl<T> ResultSink<T> for Vec<T> {
+    fn emit(&mut self, result: T) {
+        self.push(result);
+    }
+}
+
+/// 別スレッドへ流す。受信側が閉じていたら捨てる
+impl<T> ResultSink<T> for Sender<T> {
+    fn emit(&mut self, result: T) {
+        let _ = self.send(result);
+    }
+}
+
+impl<T> ResultSink<T> for SyncSender<T> {
+    fn emit(&mut self, result: T) {
+        let _ = self.send(result);
+    }
+}
+
+impl<T, S: ResultSink<T> + ?Sized> ResultSink<T> for &mut S {
+    fn emit(&mut self, result: T) {
+        (**self).emit(result);
+    }
+}
+
+/// クロージャを sink として使うためのラッパ
+pub struct FnSink<F>(F);
+
+pub fn from_fn<T, F: FnMut(T)>(f: F) -> FnSink<F> {
+    FnSink(f)
+}
+
+impl<T, F: FnMut(T)> ResultSink<T> for FnSink<F> {
+    fn emit(&mut self, result: T) {
+        (self.0)(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn emit_all<S: ResultSink<u32>>(sink: &mut S) {
+        for i in 0..3 {
+            sink.emit(i);
+        }
+    }
+
+    #[test]
+    fn vec_sink_collects() {
+        let mut out = Vec::new();
+        emit_all(&mut out);
+        assert_eq!(out, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn channel_sink_forwards() {
+        let (mut tx, rx) = mpsc::channel();
+        emit_all(&mut tx);
+        drop(tx);
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn fn_sink_calls_closure() {
+        let mut sum = 0;
+        emit_all(&mut from_fn(|v| sum += v));
+        assert_eq!(sum, 3);
+    }
+}
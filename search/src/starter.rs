@@ -0,0 +1,95 @@
+use rng_core::gpu::input_layout::GpuIvConfig;
+use rng_core::error::ConfigError;
+use rng_core::lcg::nature::Nature;
+
+use crate::error::SearchError;
+
+/**
+御三家検索の条件。性格と、その性格で狙う IV の範囲 (H,A,B,C,D,S の順)
+
+ツタージャ・ポカブ・ミジュマルのどれを選んでも乱数の消費は変わらないので、御三家の種類は持たない
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarterRequest {
+    pub nature: Nature,
+    pub iv_min: [u8; 6],
+    pub iv_max: [u8; 6],
+}
+
+impl StarterRequest {
+    pub fn new(nature: Nature, iv_min: [u8; 6], iv_max: [u8; 6]) -> Result<Self, SearchError> {
+        if nature.id() >= Nature::MAX {
+            return Err(ConfigError::InvalidNature(nature.id()).into());
+        }
+        let valid = iv_min.iter().zip(iv_max.iter()).all(|(&lo, &hi)| lo <= hi && hi <= 31);
+        if !valid {
+            return Err(SearchError::InvalidIvRange { iv_min, iv_max });
+        }
+        Ok(Self { nature, iv_min, iv_max })
+    }
+
+    /**
+    今まで使ってきた IV 条件
+    - やんちゃ: 物理アタッカー向け、S は 25 固定
+    - うっかりや: 両刀向け
+
+    それ以外の性格はプリセットがないので、範囲を指定して `new` を使う
+    */
+    pub fn preset(nature: Nature) -> Result<Self, SearchError> {
+        match nature.id() {
+            4 => Self::new(nature, [27, 29, 29, 29, 0, 25], [31, 31, 31, 31, 31, 25]),
+            19 => Self::new(nature, [28, 29, 30, 30, 0, 30], [31, 31, 31, 31, 31, 31]),
+            any => Err(SearchError::UnsupportedNature(any)),
+        }
+    }
+
+    pub fn iv_matches(&self, ivs: [u8; 6]) -> bool {
+        (0..6).all(|i| (self.iv_min[i]..=self.iv_max[i]).contains(&ivs[i]))
+    }
+
+    pub fn gpu_iv_config(&self, iv_step: u32) -> GpuIvConfig {
+        GpuIvConfig {
+            iv_step,
+            _pad0: 0,
+            iv_min: self.iv_min.map(u32::from),
+            iv_max: self.iv_max.map(u32::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_matches_previous_checks() {
+        let naughty = StarterRequest::preset(Nature::new(4)).unwrap();
+        assert!(naughty.iv_matches([31, 30, 29, 31, 0, 25]));
+        assert!(!naughty.iv_matches([31, 30, 29, 31, 0, 26]));
+
+        let rash = StarterRequest::preset(Nature::new(19)).unwrap();
+        assert!(rash.iv_matches([28, 29, 30, 30, 5, 31]));
+        assert!(!rash.iv_matches([27, 29, 30, 30, 5, 31]));
+
+        assert!(matches!(
+            StarterRequest::preset(Nature::new(3)),
+            Err(SearchError::UnsupportedNature(3))
+        ));
+    }
+
+    #[test]
+    fn new_validates_bounds() {
+        let timid = StarterRequest::new(Nature::new(10), [0, 0, 0, 30, 0, 31], [31; 6]).unwrap();
+        assert!(timid.iv_matches([3, 0, 12, 30, 7, 31]));
+        assert_eq!(timid.gpu_iv_config(17).iv_min, [0, 0, 0, 30, 0, 31]);
+
+        assert!(matches!(
+            StarterRequest::new(Nature::new(10), [31; 6], [30; 6]),
+            Err(SearchError::InvalidIvRange { .. })
+        ));
+        assert!(matches!(
+            StarterRequest::new(Nature::new(25), [0; 6], [31; 6]),
+            Err(SearchError::Config(ConfigError::InvalidNature(25)))
+        ));
+    }
+}
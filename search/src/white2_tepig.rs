@@ -1,17 +1,26 @@
-﻿use core::panic;
-use std::collections::HashSet;
+﻿use std::collections::HashSet;
 use std::thread;
 
+use infra::file::CsvRecord;
 use infra::gpu::context::GpuContext;
 use rayon::prelude::*;
 use rng_core::gpu::helpers::{GpuInputParams, run_result_base_seedhigh_by_dates_multi_iv};
-use rng_core::gpu::input_layout::GpuIvConfig;
+use rng_core::initial_seed::result_base_by_dates_cpu;
 use rng_core::lcg::{Lcg, OffsetType};
 use rng_core::lcg::grotto::Grottos;
 use rng_core::lcg::nature::Nature as Nature;
 use rng_core::lcg::wild_poke::WildPoke;
 use rng_core::models::DSConfig as DSConfig;
-use rng_core::models::game_date::{GameDate, build_date_except_summer};
+use rng_core::models::{DateSet, KeyPressFilter, KeyPresses, Season, TimeSet};
+use rng_core::models::game_date::GameDate;
+use rng_core::models::GameTime;
+use rng_core::result_base::ResultBase;
+
+use crate::error::SearchError;
+use crate::progress::{ProgressTracker, SearchControl};
+use crate::robustness::{self, Neighbourhood, Robustness};
+use crate::sink::ResultSink;
+use crate::starter::StarterRequest;
 
 #[derive(Debug,Clone)]
 pub struct TepigSearchResult {
@@ -24,13 +33,15 @@ pub struct TepigSearchResult {
     pub minute: u8,
     pub second: u8,
     pub tid: u16,
-    pub key_presses: String,
+    pub key_presses: KeyPresses,
     pub ivs: [u8; 6],
     pub tepig_iv_step: u8,
     pub tepig_frames: Vec<u32>,
     pub candy_frames: Vec<(u32, Grottos)>,
     pub pidove_frames: Vec<(u32, WildPoke)>,
     pub psyduck_frames: Vec<(u32, WildPoke)>,
+    /// [`robustness`] で計算したときだけ入る
+    pub robustness: Option<Robustness>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,272 +65,345 @@ const GROTTO_SLOT: u32 = 60;
 const GROTTO_INDEX_2: usize = 19;
 const GROTTO_SLOT_2: u32 = 0;
 
-/**
-やんちゃの個体値かどうかを確かめる
-*/
-fn tepig_iv_check_naughty(ivs: [u8; 6]) -> bool {
-    (27..=31).contains(&ivs[0]) // HP
-    && (29..=31).contains(&ivs[1]) // A
-    && (29..=31).contains(&ivs[2]) // B
-    && (29..=31).contains(&ivs[3]) // C
-    && ivs[5] == 25 // S
-} 
+const BATCH_DATES: usize = 256;
+/// CPU 版で進捗を報告・中断を確かめる間隔 (日付は並列に回す)
+const CPU_BATCH_DATES: usize = 8;
+
+pub async fn white2_tepig_dragonite_search(config: DSConfig, nat: Nature, mode: BW2Mode)
+    -> Result<Vec<TepigSearchResult>, SearchError> {
+    let mut results = Vec::new();
+    let request = StarterRequest::preset(nat)?;
+    white2_tepig_dragonite_search_with_sink(config, &DateSet::all(), &request, &KeyPressFilter::all(), mode, &mut results, &mut SearchControl::new()).await?;
+    Ok(results)
+}
 
 /**
-うっかりやに適した個体値かどうかを確かめる
+見つかった結果を順次 `sink` に流す版。性格と IV 条件は `request`、キー入力は `key_filter` で指定する
+
+`dates` のうち夏以外の日付を探す (夏は御三家を受け取れない)
 */
-fn tepig_iv_check_rash(ivs: [u8; 6]) -> bool {
-    (28..=31).contains(&ivs[0]) // HP
-    && (29..=31).contains(&ivs[1]) // A
-    && (30..=31).contains(&ivs[2]) // B
-    && (30..=31).contains(&ivs[3]) // C
-    && (30..=31).contains(&ivs[5]) // S
+pub async fn white2_tepig_dragonite_search_with_sink(
+    config: DSConfig,
+    dates: &DateSet,
+    request: &StarterRequest,
+    key_filter: &KeyPressFilter,
+    mode: BW2Mode,
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let dates = dates.clone().except(DateSet::season(Season::Summer)).dates();
+    tepig_search_by_dates(config, request, key_filter, &dates, mode, find_grotto_advances_candy_dragonite, sink, control).await
 }
 
-fn tepig_iv_check(ivs: [u8; 6], nat: &Nature) -> bool {
-    match nat.id() {
-        4 => tepig_iv_check_naughty(ivs),
-        19 => tepig_iv_check_rash(ivs),
-        any => panic!("Invalid Nature: {}", any.to_string()),
-    }
+pub async fn white2_tepig_search(config: DSConfig, year: u8, month: u8, day: u8, nat: Nature, mode: BW2Mode)
+    -> Result<Vec<TepigSearchResult>, SearchError> {
+    let mut results = Vec::new();
+    let request = StarterRequest::preset(nat)?;
+    white2_tepig_search_with_sink(config, year, month, day, &request, &KeyPressFilter::all(), mode, &mut results, &mut SearchControl::new()).await?;
+    Ok(results)
 }
 
-const BATCH_DATES: usize = 256;
-
-pub async fn white2_tepig_dragonite_search(config: DSConfig, nat: Nature, mode: BW2Mode)
-    -> Vec<TepigSearchResult> {
-    let dates = build_date_except_summer();
-    tepig_search_by_dates(config, nat, &dates, mode, find_grotto_advances_candy_dragonite).await
+/// 見つかった結果を順次 `sink` に流す版。性格と IV 条件は `request`、キー入力は `key_filter` で指定する
+#[allow(clippy::too_many_arguments)]
+pub async fn white2_tepig_search_with_sink(
+    config: DSConfig,
+    year: u8,
+    month: u8,
+    day: u8,
+    request: &StarterRequest,
+    key_filter: &KeyPressFilter,
+    mode: BW2Mode,
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let dates = [searchable_date(year, month, day)?];
+    tepig_search_by_dates(config, request, key_filter, &dates, mode, find_grotto_advances_candy, sink, control).await
 }
 
-pub async fn white2_tepig_search(config: DSConfig, year: u8, month: u8, day: u8, nat: Nature, mode: BW2Mode)
-    -> Vec<TepigSearchResult> {
-    if year >= 100 || month > 12 || day > 31 {
-        panic!("Invalid Date!")
-    };
+/**
+`white2_tepig_search_with_sink` の GPU を使わない版
 
-    if month % 4 == 2{
-        panic!("SUMMER MONTH!")
-    };
+SHA-1 は `LANES` 件ずつまとめて CPU で計算し、IV は 16・17 消費の両方を MT で確かめる
+GPU が使えない環境でも同じ結果が得られるが、全キー入力だと1日あたり数億件の計算になる
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn white2_tepig_search_cpu_with_sink(
+    config: DSConfig,
+    year: u8,
+    month: u8,
+    day: u8,
+    request: &StarterRequest,
+    key_filter: &KeyPressFilter,
+    mode: BW2Mode,
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let dates = [searchable_date(year, month, day)?];
+    tepig_search_by_dates_cpu(config, request, key_filter, &dates, mode, find_grotto_advances_candy, sink, control)
+}
 
-    let dates = [GameDate{ year, month, day }];
-    tepig_search_by_dates(config, nat, &dates, mode, find_grotto_advances_candy).await
+/// 存在しない日付と夏の日付を弾く
+fn searchable_date(year: u8, month: u8, day: u8) -> Result<GameDate, SearchError> {
+    let date = GameDate::try_new(year, month, day)?;
+    if Season::of_month(month) == Season::Summer {
+        return Err(SearchError::SummerDate { month });
+    };
+    Ok(date)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn tepig_search_by_dates(
     config: DSConfig,
-    nat: Nature,
+    request: &StarterRequest,
+    key_filter: &KeyPressFilter,
     dates: &[GameDate],
     mode: BW2Mode,
     find_grotto: fn(u64, u64, u64) -> Vec<(u32, Grottos)>,
-)
-    -> Vec<TepigSearchResult> {
-    let ctx = GpuContext::new().await;
-    let mut results = Vec::new();
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let ctx = GpuContext::new().await?;
+    // 重複排除用。ヒットした seed0 だけを覚える
     let mut seen_seed0: HashSet<u64> = HashSet::new();
     let mut pending_cpu: Option<thread::JoinHandle<Vec<TepigSearchResult>>> = None;
 
-    let (iv_min, iv_max): ([u32; 6], [u32; 6]) = match nat.id() {
-        4 => ([27, 29, 29, 29, 0, 25], [31, 31, 31, 31, 31, 25]), // Naughty
-        19 => ([28, 29, 30, 30, 0, 30], [31, 31, 31, 31, 31, 31]), // Rash
-        any => panic!("Invalid Nature: {}", any.to_string()),
-    };
-
+    let iv_cfgs = [request.gpu_iv_config(16), request.gpu_iv_config(17)];
     let params = GpuInputParams::new(
         config,
         [0, 23],
         [0, 59],
         [0, 59],
         16,
-        iv_min,
-        iv_max,
-    );
-    let iv_cfgs = [
-        GpuIvConfig {
-            iv_step: 16,
-            _pad0: 0,
-            iv_min,
-            iv_max,
-        },
-        GpuIvConfig {
-            iv_step: 17,
-            _pad0: 0,
-            iv_min,
-            iv_max,
-        },
-    ];
-
-    let mut date_batch = Vec::with_capacity(BATCH_DATES);
-    for &date in dates {
-        date_batch.push(date);
-        if date_batch.len() < BATCH_DATES {
-            continue;
+        iv_cfgs[0].iv_min,
+        iv_cfgs[0].iv_max,
+    )
+    .with_result_limit(control.candidate_limit())
+    .with_key_filter(key_filter.clone());
+
+    let mut tracker = control.tracker(dates.len(), params.seeds_per_date());
+
+    let mut gpu_error = None;
+
+    // GPU で次のバッチを回している間に、前のバッチを CPU スレッドで絞り込む
+    for date_batch in dates.chunks(BATCH_DATES) {
+        if control.is_cancelled() {
+            break;
         }
         let base_results = match run_result_base_seedhigh_by_dates_multi_iv(
             &ctx,
             config,
             &params,
-            &date_batch,
+            date_batch,
             BATCH_DATES,
             &iv_cfgs,
         ).await {
             Ok(v) => v,
-            Err(_) => {
-                date_batch.clear();
-                continue;
+            Err(e) => {
+                gpu_error = Some(e);
+                break;
             }
         };
-        if let Some(handle) = pending_cpu.take() {
-            let batch_results = handle.join().expect("CPU worker thread panicked");
-            merge_results(batch_results, &mut results, &mut seen_seed0);
+        tracker.dates_done(date_batch.len(), base_results.len());
+        if let Err(e) = control.check_candidates(&tracker) {
+            gpu_error = Some(e);
+            break;
         }
-        let nat_clone = nat.clone();
-        pending_cpu = Some(thread::spawn(move || {
-            process_base_results(base_results, mode, nat_clone, find_grotto)
-        }));
-        date_batch.clear();
-    }
-    if !date_batch.is_empty() {
-        let base_results = match run_result_base_seedhigh_by_dates_multi_iv(
-            &ctx,
-            config,
-            &params,
-            &date_batch,
-            BATCH_DATES,
-            &iv_cfgs,
-        ).await {
-            Ok(v) => v,
-            Err(_) => Vec::new(),
-        };
         if let Some(handle) = pending_cpu.take() {
             let batch_results = handle.join().expect("CPU worker thread panicked");
-            merge_results(batch_results, &mut results, &mut seen_seed0);
+            emit_results(batch_results, &mut seen_seed0, sink, &mut tracker);
         }
-        let nat_clone = nat.clone();
+        control.report(&mut tracker);
+        let request = request.clone();
         pending_cpu = Some(thread::spawn(move || {
-            process_base_results(base_results, mode, nat_clone, find_grotto)
+            process_base_results(base_results, mode, &request, find_grotto)
         }));
     }
+    // 中断・エラーの場合も、GPU で済んだバッチの結果は流しておく
     if let Some(handle) = pending_cpu.take() {
         let batch_results = handle.join().expect("CPU worker thread panicked");
-        merge_results(batch_results, &mut results, &mut seen_seed0);
+        emit_results(batch_results, &mut seen_seed0, sink, &mut tracker);
+        control.report(&mut tracker);
     }
 
-    results
+    match gpu_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
 }
 
-fn merge_results(
+#[allow(clippy::too_many_arguments)]
+fn tepig_search_by_dates_cpu(
+    config: DSConfig,
+    request: &StarterRequest,
+    key_filter: &KeyPressFilter,
+    dates: &[GameDate],
+    mode: BW2Mode,
+    find_grotto: fn(u64, u64, u64) -> Vec<(u32, Grottos)>,
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    control: &mut SearchControl<'_>,
+) -> Result<(), SearchError> {
+    let times = TimeSet::all();
+    let mut seen_seed0: HashSet<u64> = HashSet::new();
+    let mut tracker = control.tracker(dates.len(), times.len() as u64 * key_filter.iter().count() as u64);
+
+    for date_batch in dates.chunks(CPU_BATCH_DATES) {
+        if control.is_cancelled() {
+            break;
+        }
+        // GPU 版の seed_high の絞り込みと同じく、16・17 消費のどちらかで IV が合うものを残す
+        let base_results = result_base_by_dates_cpu(config, date_batch, &times, key_filter, 16, |base| {
+            request.iv_matches(base.ivs) || request.iv_matches(rng_core::mt::mt_1(base.seed1, 17))
+        });
+        tracker.dates_done(date_batch.len(), base_results.len());
+        control.check_candidates(&tracker)?;
+        let batch_results = process_base_results(base_results, mode, request, find_grotto);
+        emit_results(batch_results, &mut seen_seed0, sink, &mut tracker);
+        control.report(&mut tracker);
+    }
+    Ok(())
+}
+
+fn emit_results(
     batch_results: Vec<TepigSearchResult>,
-    results: &mut Vec<TepigSearchResult>,
     seen_seed0: &mut HashSet<u64>,
+    sink: &mut impl ResultSink<TepigSearchResult>,
+    tracker: &mut ProgressTracker,
 ) {
     for candidate in batch_results {
         if !seen_seed0.insert(candidate.seed0) {
             continue;
         }
-        results.push(candidate);
+        sink.emit(candidate);
+        tracker.results_found(1);
     }
 }
 
 fn process_base_results(
-    base_results: Vec<rng_core::result_base::ResultBase>,
+    base_results: Vec<ResultBase>,
     mode: BW2Mode,
-    nat: Nature,
+    request: &StarterRequest,
     find_grotto: fn(u64, u64, u64) -> Vec<(u32, Grottos)>,
     )
     -> Vec<TepigSearchResult> {
     base_results
         .into_par_iter()
-        .filter_map(|base| {
-            let seed0 = base.seed0;
-            let seed1 = base.seed1;
-
-            let mut rng: Lcg = Lcg::new(seed0);
-            let offset = match mode {
-                BW2Mode::Normal => rng.offset_seed0(OffsetType::BW2Start),
-                BW2Mode::Challenge => rng.offset_seed0(OffsetType::BW2StartChallengeMode),
-            };
-
-            let candy_frames = find_grotto(seed0, FRAME_MIN_FOR_CANDY, FRAME_MAX_FOR_CANDY);
-            if candy_frames.is_empty() {
-                return None;
-            }
+        .filter_map(|base| evaluate_base(&base, mode, request, find_grotto))
+        .collect()
+}
 
-            let ivs_16: [u8; 6] = base.ivs;
-            let ivs_17: [u8; 6] = rng_core::mt::mt_1(seed1, 17);
-            let ivs: [u8; 6];
-            let tepig_iv_frame: u8;
-
-            if tepig_iv_check(ivs_16, &nat) {
-                ivs = ivs_16;
-                tepig_iv_frame = 16;
-            } else if tepig_iv_check(ivs_17, &nat) {
-                ivs = ivs_17;
-                tepig_iv_frame = 17;
-            } else {
-                return None;
-            }
+/// 1つの seed を絞り込む。IV は `base.ivs` を 16 消費、17 消費は seed1 から計算し直す
+fn evaluate_base(
+    base: &ResultBase,
+    mode: BW2Mode,
+    request: &StarterRequest,
+    find_grotto: fn(u64, u64, u64) -> Vec<(u32, Grottos)>,
+) -> Option<TepigSearchResult> {
+    let seed0 = base.seed0;
+    let seed1 = base.seed1;
+
+    let mut rng: Lcg = Lcg::new(seed0);
+    let offset = match mode {
+        BW2Mode::Normal => rng.offset_seed0(OffsetType::BW2Start),
+        BW2Mode::Challenge => rng.offset_seed0(OffsetType::BW2StartChallengeMode),
+    };
 
-            if tepig_iv_frame == 17 {
-                rng.next();
-            }
+    let candy_frames = find_grotto(seed0, FRAME_MIN_FOR_CANDY, FRAME_MAX_FOR_CANDY);
+    if candy_frames.is_empty() {
+        return None;
+    }
 
-            let tid = rng.tid_sid(OffsetType::BW2Start).0;
+    let ivs_16: [u8; 6] = base.ivs;
+    let ivs_17: [u8; 6] = rng_core::mt::mt_1(seed1, 17);
+    let ivs: [u8; 6];
+    let tepig_iv_frame: u8;
+
+    if request.iv_matches(ivs_16) {
+        ivs = ivs_16;
+        tepig_iv_frame = 16;
+    } else if request.iv_matches(ivs_17) {
+        ivs = ivs_17;
+        tepig_iv_frame = 17;
+    } else {
+        return None;
+    }
 
-            rng.advance(MIN_TEPIG_NATURE - 1);
+    if tepig_iv_frame == 17 {
+        rng.next();
+    }
 
-            let mut tepig_frames = Vec::new();
-            for frame in MIN_TEPIG_NATURE..=MAX_TEPIG_NATURE {
-                if rng.get_nature() == nat {
-                    tepig_frames.push((frame + offset & 0xFFFFFFFF) as u32);
-                }
-            }
-            if tepig_frames.is_empty() {
-                return None;
-            }
+    let tid = rng.tid_sid(OffsetType::BW2Start).expect("offset applied above").0;
 
-            let pidove_frames = find_wild_advances_bw2(
-                seed0,
-                FRAME_ENTERING_ROUTE20,
-                FRAME_EXITING_ROUTE20,
-                is_target_pidove,
-            );
-            if pidove_frames.is_empty() {
-                return None;
-            }
+    rng.advance(MIN_TEPIG_NATURE - 1);
 
-            let psyduck_frames = find_wild_advances_bw2(
-                seed0,
-                FRAME_ENTERING_RANCH,
-                FRAME_EXITING_RANCH,
-                is_target_psyduck,
-            );
-            if psyduck_frames.is_empty() {
-                return None;
-            }
+    let mut tepig_frames = Vec::new();
+    for frame in MIN_TEPIG_NATURE..=MAX_TEPIG_NATURE {
+        if rng.get_nature() == request.nature {
+            tepig_frames.push((frame + offset & 0xFFFFFFFF) as u32);
+        }
+    }
+    if tepig_frames.is_empty() {
+        return None;
+    }
 
-            
-
-            Some(TepigSearchResult {
-                seed0,
-                seed1,
-                year: base.game_time.year,
-                month: base.game_time.month,
-                day: base.game_time.day,
-                hour: base.game_time.hour,
-                minute: base.game_time.minute,
-                second: base.game_time.second,
-                tid,
-                key_presses: base.key_presses.pressed_keys_string(),
-                ivs,
-                tepig_iv_step: tepig_iv_frame,
-                tepig_frames,
-                candy_frames,
-                pidove_frames,
-                psyduck_frames,
-            })
-        })
-        .collect()
+    let pidove_frames = find_wild_advances_bw2(
+        seed0,
+        FRAME_ENTERING_ROUTE20,
+        FRAME_EXITING_ROUTE20,
+        is_target_pidove,
+    );
+    if pidove_frames.is_empty() {
+        return None;
+    }
+
+    let psyduck_frames = find_wild_advances_bw2(
+        seed0,
+        FRAME_ENTERING_RANCH,
+        FRAME_EXITING_RANCH,
+        is_target_psyduck,
+    );
+    if psyduck_frames.is_empty() {
+        return None;
+    }
+
+    Some(TepigSearchResult {
+        seed0,
+        seed1,
+        year: base.game_time.year(),
+        month: base.game_time.month(),
+        day: base.game_time.day(),
+        hour: base.game_time.hour(),
+        minute: base.game_time.minute(),
+        second: base.game_time.second(),
+        tid,
+        key_presses: base.key_presses,
+        ivs,
+        tepig_iv_step: tepig_iv_frame,
+        tepig_frames,
+        candy_frames,
+        pidove_frames,
+        psyduck_frames,
+        robustness: None,
+    })
+}
+
+/**
+`result` の前後の秒・他の Timer0・キーの押し間違いでも、検索と同じ条件で当たるかを調べる
+
+`dragonite` は `white2_tepig_dragonite_search` の結果かどうか (秘密の穴の条件が変わる)
+*/
+pub fn robustness(
+    config: DSConfig,
+    result: &TepigSearchResult,
+    request: &StarterRequest,
+    mode: BW2Mode,
+    dragonite: bool,
+    neighbourhood: &Neighbourhood,
+) -> Robustness {
+    let find_grotto = if dragonite { find_grotto_advances_candy_dragonite } else { find_grotto_advances_candy };
+    let game_time = GameTime::new(result.year, result.month, result.day, result.hour, result.minute, result.second);
+    robustness::evaluate(config, &game_time, result.key_presses, neighbourhood, 16, |base| {
+        evaluate_base(base, mode, request, find_grotto).is_some()
+    })
 }
 
 
@@ -408,6 +492,80 @@ fn find_grotto_advances_candy_dragonite(seed0: u64, start: u64, end: u64) -> Vec
 }
 
 
+impl CsvRecord for TepigSearchResult {
+    fn header() -> &'static [&'static str] {
+        &[
+            "seed0", "seed1",
+            "year", "month", "day", "hour", "minute", "second",
+            "tid", "key_presses",
+            "iv_h", "iv_a", "iv_b", "iv_c", "iv_d", "iv_s",
+            "tepig_iv_step", "tepig_frames",
+            "candy_frames", "pidove_frames", "psyduck_frames",
+            "score", "neighbourhood",
+        ]
+    }
+
+    /**
+    フレームのリストは1セルにまとめる
+    - tepig_frames: `201;205`
+    - candy_frames: `362:#3(0/60/12) #19(2/0/44);363:...`
+    - pidove_frames: `430:Lv.2:Naughty;...`
+    - psyduck_frames: `551:Modest;...`
+
+    score と neighbourhood は取りやすさを計算したときだけ埋まる
+    */
+    fn row(&self) -> Vec<String> {
+        let mut row = vec![
+            format!("0x{:016X}", self.seed0),
+            format!("0x{:016X}", self.seed1),
+            self.year.to_string(),
+            self.month.to_string(),
+            self.day.to_string(),
+            self.hour.to_string(),
+            self.minute.to_string(),
+            self.second.to_string(),
+            self.tid.to_string(),
+            self.key_presses.to_string(),
+        ];
+        row.extend(self.ivs.iter().map(|iv| iv.to_string()));
+        row.push(self.tepig_iv_step.to_string());
+        row.push(join_cells(self.tepig_frames.iter().map(|f| f.to_string())));
+        row.push(join_cells(self.candy_frames.iter().map(|(frame, grottos)| {
+            format!("{}:{}", frame, encode_grottos(grottos))
+        })));
+        row.push(join_cells(self.pidove_frames.iter().map(|(frame, poke)| {
+            let lv = if poke.slot.is_some_and(|s| s < 20) { "Lv.2" } else { "Lv.4" };
+            format!("{}:{}:{}", frame, lv, nature_name(poke))
+        })));
+        row.push(join_cells(self.psyduck_frames.iter().map(|(frame, poke)| {
+            format!("{}:{}", frame, nature_name(poke))
+        })));
+        row.push(self.robustness.as_ref().map(|r| format!("{:.4}", r.score)).unwrap_or_default());
+        row.push(self.robustness.as_ref().map(Robustness::map).unwrap_or_default());
+        row
+    }
+}
+
+fn join_cells(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(";")
+}
+
+fn nature_name(poke: &WildPoke) -> &'static str {
+    poke.nature.as_ref().map(|n| n.name()).unwrap_or("None")
+}
+
+/// 埋まっている穴だけを `#index(sub/slot/gender)` で並べる
+fn encode_grottos(grottos: &Grottos) -> String {
+    let mut out = Vec::new();
+    for i in 0..grottos.grottos.len() {
+        let g = grottos.get(i).unwrap_or_default();
+        if let (Some(sub), Some(slot), Some(gender)) = (g.sub_slot(), g.slot(), g.gender()) {
+            out.push(format!("#{}({}/{}/{})", i, sub, slot, gender));
+        }
+    }
+    out.join(" ")
+}
+
 impl TepigSearchResult {
     #[cfg(test)]
     fn print(&self) {
@@ -463,15 +621,123 @@ impl TepigSearchResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rng_core::models::{Hardware, Key};
+    use rng_core::sha_1::generate_initial_seed0;
     use std::time::Instant;
 
+    fn sample_result() -> TepigSearchResult {
+        let seed0 = 0x113E10468C85C156;
+        let mut lcg = Lcg::new(seed0);
+        lcg.advance(395);
+        let mut grottos = Grottos::new();
+        grottos.fill_grottos(&lcg);
+        TepigSearchResult {
+            seed0,
+            seed1: Lcg::new(seed0).next(),
+            year: 33,
+            month: 8,
+            day: 27,
+            hour: 12,
+            minute: 34,
+            second: 56,
+            tid: 12345,
+            key_presses: KeyPresses::from_keys(&[Key::A, Key::Start]),
+            ivs: [31, 30, 30, 31, 12, 25],
+            tepig_iv_step: 16,
+            tepig_frames: vec![201, 205],
+            candy_frames: vec![(395, grottos)],
+            pidove_frames: vec![(
+                430,
+                WildPoke { slot: Some(12), poke_code: Some(0), nature: Some(Nature::new(4)), item: Some(0) },
+            )],
+            psyduck_frames: vec![],
+            robustness: None,
+        }
+    }
+
+    #[test]
+    fn csv_row_encodes_frame_lists() {
+        let row = sample_result().row();
+        assert_eq!(row.len(), TepigSearchResult::header().len());
+        assert_eq!(row[0], "0x113E10468C85C156");
+        assert_eq!(row[9], "A + START");
+        assert_eq!(row[17], "201;205");
+        assert!(row[18].starts_with("395:#"), "candy cell: {}", row[18]);
+        assert_eq!(row[19], "430:Lv.2:Naughty");
+        assert_eq!(row[20], "");
+        assert_eq!(row[21], "");
+    }
+
+    #[test]
+    fn robustness_finds_known_neighbouring_hits() {
+        // 33/08/27 キー入力なしでは 10FA 12:32:13 と 10FB 12:31:00 が当たる (その前後 1 秒・±1 の Timer0 では当たらない)
+        let config = DSConfig::new(rng_core::models::GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce);
+        let request = StarterRequest::new(Nature::new(4), [0; 6], [31; 6]).unwrap();
+        let no_keys = KeyPresses::new(0x2fff);
+        let hit_a = GameTime::new(33, 8, 27, 12, 32, 13);
+        let hit_b = GameTime::new(33, 8, 27, 12, 31, 0);
+        assert_eq!(generate_initial_seed0(&config, &hit_a, no_keys), 0xB38133D93163C727);
+        assert_eq!(
+            generate_initial_seed0(&DSConfig { Timer0: 0x10FB, ..config }, &hit_b, no_keys),
+            0xB54E86DF80D5F121
+        );
+
+        let at = |time: GameTime| TepigSearchResult {
+            hour: time.hour(),
+            minute: time.minute(),
+            second: time.second(),
+            key_presses: no_keys,
+            ..sample_result()
+        };
+        let n = Neighbourhood::new(&config).with_timer0(0x10F9..=0x10FB);
+
+        let centred = robustness(config, &at(hit_a), &request, BW2Mode::Normal, false, &n);
+        assert!(centred.map().starts_with("10F9 ... | 10FA .#. | 10FB ... |"), "{}", centred.map());
+
+        // 1 秒遅く押して Timer0 が 1 大きくても当たる
+        let shifted = robustness(config, &at(GameTime::new(33, 8, 27, 12, 31, 1)), &request, BW2Mode::Normal, false, &n);
+        assert!(shifted.map().starts_with("10F9 ... | 10FA ... | 10FB #.. |"), "{}", shifted.map());
+        let hit = shifted.cells.iter().find(|c| c.hit && !c.key_slip).unwrap();
+        assert_eq!((hit.second_offset, hit.timer0), (-1, 0x10FB));
+    }
+
+    #[test]
+    fn cpu_search_finds_a_known_hit() {
+        // robustness_finds_known_neighbouring_hits の 10FA 12:32:13 (キー入力なし)
+        let config = DSConfig::new(rng_core::models::GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce);
+        let request = StarterRequest::new(Nature::new(4), [0; 6], [31; 6]).unwrap();
+        let filter = KeyPressFilter::all().max_keys(0);
+        let mut results = Vec::new();
+        white2_tepig_search_cpu_with_sink(
+            config, 33, 8, 27, &request, &filter, BW2Mode::Normal, &mut results, &mut SearchControl::new(),
+        )
+        .unwrap();
+
+        let hit = results.iter().find(|r| r.seed0 == 0xB38133D93163C727).expect("known hit");
+        assert_eq!((hit.hour, hit.minute, hit.second), (12, 32, 13));
+        assert_eq!(hit.key_presses, KeyPresses::new(0x2fff));
+        assert!(results.iter().all(|r| r.key_presses == hit.key_presses));
+    }
+
+    #[test]
+    fn invalid_input_is_rejected_before_gpu() {
+        let ds_config = DSConfig::new(rng_core::models::GameVersion::White2, 0x10FA, Hardware::DsPhat, 0x0009bf6d93ce);
+        let search = |y, m, d, nat| {
+            pollster::block_on(white2_tepig_search(ds_config, y, m, d, Nature::new(nat), BW2Mode::Normal))
+        };
+
+        assert!(matches!(search(33, 2, 30, 4), Err(SearchError::Config(_))));
+        assert!(matches!(search(33, 6, 1, 4), Err(SearchError::SummerDate { month: 6 })));
+        assert!(matches!(search(33, 8, 27, 3), Err(SearchError::UnsupportedNature(3))));
+    }
+
     #[test]
     #[ignore]
     fn test_white2_tepig_single_date() {
         let ds_config = DSConfig{
             Version : rng_core::models::GameVersion::White2,
             Timer0 : 0x10FA,
-            IsDSLite : false,
+            Hardware: Hardware::DsPhat,
             MAC : 0x0009bf6d93ce,
         };
 
@@ -485,7 +751,7 @@ mod tests {
                 Nature::new(4),
                 BW2Mode::Normal
             ).await // 例: Rash
-        });
+        }).expect("search failed");
         let elapsed = start.elapsed();
 
         println!("Elapsed: {:?}", elapsed);
@@ -500,7 +766,7 @@ mod tests {
         let ds_config = DSConfig{
             Version : rng_core::models::GameVersion::White2,
             Timer0 : 0x10FA,
-            IsDSLite : false,
+            Hardware: Hardware::DsPhat,
             MAC : 0x0009bf6d93ce,
         };
 
@@ -510,7 +776,7 @@ mod tests {
                 Nature::new(4),
                 BW2Mode::Normal
             ).await // 例: Naughty
-        });
+        }).expect("search failed");
         let elapsed = start.elapsed();
 
         println!("Elapsed: {:?}", elapsed);
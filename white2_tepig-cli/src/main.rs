@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::io::{self, Write};
@@ -6,22 +5,15 @@ use std::path::PathBuf;
 
 use rng_core::lcg::TID_impl::get_frigate_pass;
 use rng_core::lcg::nature::Nature;
-use rng_core::models::ds_config::DSConfig;
+use rng_core::models::ds_config::{DSConfig, DsConfigFile};
 use rng_core::models::game_version::GameVersion;
 use search::white2_tepig::{BW2Mode, white2_tepig_search};
 use search::white2_tepig::TepigSearchResult;
-use serde::Deserialize;
-
-
-#[derive(Deserialize)]
-struct DsConfigFile {
-    ds_configs: HashMap<String, DSConfig>,
-}
 
 fn main() -> Result<(), Box<dyn Error>> {
     let default_config = default_config_path();
     let config_path = prompt_path("ds_config.json path", &default_config)?;
-    let ds_config = load_single_profile(&config_path)?;
+    let ds_config = prompt_profile(&config_path)?;
 
     if ds_config.Version != GameVersion::White2 {
         eprintln!(
@@ -38,7 +30,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let (year, month, day) = prompt_date()?;
     let results =
-        pollster::block_on(async { white2_tepig_search(ds_config, year, month, day, nature, mode).await });
+        pollster::block_on(async { white2_tepig_search(ds_config, year, month, day, nature, mode).await })?;
 
     let output_path = default_output_path();
     let text = build_text(&results);
@@ -48,22 +40,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn load_config_file(path: &PathBuf) -> Result<DsConfigFile, Box<dyn Error>> {
-    let text = fs::read_to_string(path)?;
-    let file: DsConfigFile = serde_json::from_str(&text)?;
-    Ok(file)
-}
-
-fn load_single_profile(path: &PathBuf) -> Result<DSConfig, Box<dyn Error>> {
-    let file = load_config_file(path)?;
-    let mut iter = file.ds_configs.iter();
-    let (name, cfg) = iter
-        .next()
-        .ok_or("no profiles found in ds_config.json")?;
-    if iter.next().is_some() {
-        eprintln!("warning: multiple profiles found; using '{}'", name);
+fn prompt_profile(path: &PathBuf) -> Result<DSConfig, Box<dyn Error>> {
+    let file = DsConfigFile::load(path)?;
+    let names: Vec<&str> = file.names().collect();
+    let first = *names.first().ok_or("no profiles found in ds_config.json")?;
+    if names.len() == 1 {
+        return Ok(file.ds_configs[first]);
     }
-    Ok(*cfg)
+    println!("profiles: {}", names.join(", "));
+    let name = prompt("profile", first)?;
+    file.get(&name)
+        .ok_or_else(|| format!("profile '{}' not found in ds_config.json", name).into())
 }
 
 fn prompt(label: &str, default: &str) -> Result<String, Box<dyn Error>> {